@@ -0,0 +1,227 @@
+//! Everything that can be triggered from outside its own window, whether by
+//! a button, a hotkey or an external control surface, goes through here.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use crossbeam_channel::{unbounded, Receiver, Sender, TryIter};
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
+
+use crate::{
+    app::{
+        storage::{self, StorageError},
+        AppState,
+    },
+    hotkey::Hotkey,
+};
+
+const KEYMAP_FILE: &str = "keymap.json";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    /// Shows the model in the library with the given file name.
+    ShowModel(String),
+    NextModel,
+    PreviousModel,
+    ResetCamera,
+    /// Forgets the current tracking data and smoothing, starting fresh from
+    /// the next packet.
+    ResetTracking,
+    /// Takes the pose currently tracked as neutral for every binding.
+    Calibrate,
+    ReconnectTracker,
+    ToggleUi,
+    Screenshot,
+    ToggleExpression(String),
+    PlayAnimation(String),
+    ToggleOutfit(String),
+}
+
+impl Action {
+    /// Actions that don't refer to anything in particular.
+    pub const GENERAL: [Action; 8] = [
+        Action::NextModel,
+        Action::PreviousModel,
+        Action::ResetCamera,
+        Action::ResetTracking,
+        Action::Calibrate,
+        Action::ReconnectTracker,
+        Action::ToggleUi,
+        Action::Screenshot,
+    ];
+
+    /// Every action that can currently be bound, given the models in the
+    /// library and the displayed model's expressions, animations and outfits.
+    pub fn available(state: &AppState) -> Vec<Action> {
+        let models: Vec<_> = state.models().model_names().collect();
+        let data = state.displayed.data();
+        let expressions = data.expressions.lock().unwrap();
+        let animations = data.animations.lock().unwrap();
+        let outfits = data.outfits.lock().unwrap();
+
+        Action::GENERAL
+            .into_iter()
+            .chain(models.into_iter().map(Action::ShowModel))
+            .chain(
+                expressions
+                    .expressions()
+                    .iter()
+                    .map(|x| Action::ToggleExpression(x.name.clone())),
+            )
+            .chain(
+                animations
+                    .clips()
+                    .iter()
+                    .map(|x| Action::PlayAnimation(x.name.clone())),
+            )
+            .chain(
+                outfits
+                    .outfits()
+                    .iter()
+                    .map(|x| Action::ToggleOutfit(x.name.clone())),
+            )
+            .collect()
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Action::ShowModel(model) => format!("Show Model: {}", model),
+            Action::NextModel => "Next Model".to_owned(),
+            Action::PreviousModel => "Previous Model".to_owned(),
+            Action::ResetCamera => "Reset Camera".to_owned(),
+            Action::ResetTracking => "Reset Tracking Data".to_owned(),
+            Action::Calibrate => "Calibrate Tracking".to_owned(),
+            Action::ReconnectTracker => "Reconnect Tracker".to_owned(),
+            Action::ToggleUi => "Toggle UI".to_owned(),
+            Action::Screenshot => "Take Screenshot".to_owned(),
+            Action::ToggleExpression(name) => format!("Toggle Expression: {}", name),
+            Action::PlayAnimation(name) => format!("Play Animation: {}", name),
+            Action::ToggleOutfit(name) => format!("Toggle Outfit: {}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    /// `None` while a new binding is waiting for its key.
+    pub hotkey: Option<Hotkey>,
+    pub action: Action,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyMap {
+    pub bindings: Vec<KeyBinding>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let binding = |key, action| KeyBinding {
+            hotkey: Some(Hotkey {
+                key,
+                ctrl: false,
+                shift: false,
+                alt: false,
+            }),
+            action,
+        };
+
+        Self {
+            bindings: vec![
+                binding(VirtualKeyCode::F1, Action::ToggleUi),
+                binding(VirtualKeyCode::F5, Action::ReconnectTracker),
+                binding(VirtualKeyCode::F12, Action::Screenshot),
+                binding(VirtualKeyCode::Home, Action::ResetCamera),
+            ],
+        }
+    }
+}
+
+impl KeyMap {
+    pub fn load() -> Result<Self, StorageError> {
+        Ok(storage::load(&storage::app_file(KEYMAP_FILE)?)?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        storage::save(&storage::app_file(KEYMAP_FILE)?, self)
+    }
+
+    pub fn actions(&self, hotkey: Hotkey) -> impl Iterator<Item = &Action> {
+        self.bindings
+            .iter()
+            .filter(move |x| x.hotkey == Some(hotkey))
+            .map(|x| &x.action)
+    }
+
+    /// Everything using each hotkey, given what else besides the key map
+    /// uses hotkeys. Any hotkey with more than one user is a conflict.
+    pub fn users(
+        &self,
+        others: impl IntoIterator<Item = (Hotkey, String)>,
+    ) -> HashMap<Hotkey, Vec<String>> {
+        let mut users: HashMap<Hotkey, Vec<String>> = HashMap::new();
+        let bindings = self
+            .bindings
+            .iter()
+            .filter_map(|x| Some((x.hotkey?, x.action.name())));
+        for (hotkey, user) in bindings.chain(others) {
+            users.entry(hotkey).or_default().push(user);
+        }
+        users
+    }
+}
+
+pub struct ActionRegistry {
+    sender: Sender<Action>,
+    receiver: Receiver<Action>,
+    keymap: Mutex<KeyMap>,
+}
+
+impl ActionRegistry {
+    pub fn new() -> Self {
+        let keymap = KeyMap::load().unwrap_or_else(|e| {
+            eprintln!("Failed to load key map: {}", e);
+            KeyMap::default()
+        });
+
+        let (sender, receiver) = unbounded();
+        Self {
+            sender,
+            receiver,
+            keymap: Mutex::new(keymap),
+        }
+    }
+
+    /// Queues an action to be performed at the start of the next frame.
+    pub fn dispatch(&self, action: Action) {
+        let _ = self.sender.send(action);
+    }
+
+    /// A handle for dispatching actions from other threads.
+    pub fn sender(&self) -> Sender<Action> {
+        self.sender.clone()
+    }
+
+    /// Dispatches the actions bound to a hotkey, returning whether there
+    /// were any.
+    pub fn key_pressed(&self, hotkey: Hotkey) -> bool {
+        let keymap = self.keymap.lock().unwrap();
+        let mut any = false;
+        for action in keymap.actions(hotkey) {
+            self.dispatch(action.clone());
+            any = true;
+        }
+        any
+    }
+
+    /// Takes every action dispatched so far.
+    pub fn drain(&self) -> TryIter<'_, Action> {
+        self.receiver.try_iter()
+    }
+
+    pub fn keymap(&self) -> MutexGuard<'_, KeyMap> {
+        self.keymap.lock().unwrap()
+    }
+}
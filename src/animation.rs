@@ -0,0 +1,358 @@
+//! Keyframed parameter animations, played on top of tracking.
+
+use std::collections::BTreeMap;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::model::Stored,
+    mixer::{LayerId, ParamMixer},
+};
+
+/// How the value moves from a keyframe to the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 5] = [
+        Interpolation::Step,
+        Interpolation::Linear,
+        Interpolation::EaseIn,
+        Interpolation::EaseOut,
+        Interpolation::EaseInOut,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Interpolation::Step => "Step",
+            Interpolation::Linear => "Linear",
+            Interpolation::EaseIn => "Ease In",
+            Interpolation::EaseOut => "Ease Out",
+            Interpolation::EaseInOut => "Ease In-Out",
+        }
+    }
+
+    fn ease(&self, t: f32) -> f32 {
+        match self {
+            Interpolation::Step => 0.0,
+            Interpolation::Linear => t,
+            Interpolation::EaseIn => t * t,
+            Interpolation::EaseOut => t * (2.0 - t),
+            Interpolation::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time: f32,
+    pub value: Vec2,
+    pub interpolation: Interpolation,
+}
+
+/// The keyframes of a single parameter, kept sorted by time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Keyframes closer than this are considered to be at the same time.
+    pub const TIME_EPSILON: f32 = 1.0 / 120.0;
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Inserts a keyframe, replacing any keyframe at the same time, and
+    /// returns its index.
+    pub fn insert(&mut self, keyframe: Keyframe) -> usize {
+        if let Some(ind) = self.find(keyframe.time) {
+            self.keyframes[ind] = keyframe;
+            return ind;
+        }
+
+        let ind = self.keyframes.partition_point(|x| x.time < keyframe.time);
+        self.keyframes.insert(ind, keyframe);
+        ind
+    }
+
+    /// The index of the keyframe at the given time, if there is one.
+    pub fn find(&self, time: f32) -> Option<usize> {
+        self.keyframes
+            .iter()
+            .position(|x| (x.time - time).abs() < Self::TIME_EPSILON)
+    }
+
+    pub fn remove(&mut self, ind: usize) -> Keyframe {
+        self.keyframes.remove(ind)
+    }
+
+    /// Moves a keyframe to a new time, returning its new index.
+    pub fn move_keyframe(&mut self, ind: usize, time: f32) -> usize {
+        let mut keyframe = self.keyframes.remove(ind);
+        keyframe.time = time;
+        self.insert(keyframe)
+    }
+
+    pub fn set_value(&mut self, ind: usize, value: Vec2) {
+        self.keyframes[ind].value = value;
+    }
+
+    pub fn set_interpolation(&mut self, ind: usize, interpolation: Interpolation) {
+        self.keyframes[ind].interpolation = interpolation;
+    }
+
+    pub fn sample(&self, time: f32) -> Option<Vec2> {
+        let next = self.keyframes.partition_point(|x| x.time <= time);
+        match (next.checked_sub(1), self.keyframes.get(next)) {
+            (None, None) => None,
+            (None, Some(first)) => Some(first.value),
+            (Some(prev), None) => Some(self.keyframes[prev].value),
+            (Some(prev), Some(next)) => {
+                let prev = &self.keyframes[prev];
+                let t = (time - prev.time) / (next.time - prev.time);
+                Some(prev.value.lerp(next.value, prev.interpolation.ease(t)))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Playback {
+    OneShot,
+    Loop,
+}
+
+impl Playback {
+    pub const ALL: [Playback; 2] = [Playback::OneShot, Playback::Loop];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Playback::OneShot => "One Shot",
+            Playback::Loop => "Loop",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Clip {
+    pub name: String,
+    /// Length in seconds.
+    pub length: f32,
+    pub playback: Playback,
+    /// Seconds taken to blend from the current pose into the clip.
+    pub blend_in: f32,
+    /// Seconds taken to blend from the clip back to the current pose.
+    pub blend_out: f32,
+    pub tracks: BTreeMap<String, Track>,
+}
+
+impl Clip {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            length: 2.0,
+            playback: Playback::OneShot,
+            blend_in: 0.2,
+            blend_out: 0.2,
+            tracks: BTreeMap::new(),
+        }
+    }
+
+    /// The blend in and out times as played. A one shot clip too short to
+    /// fit both has them shortened to share its length, so it still reaches
+    /// full weight before blending out.
+    pub fn blend_times(&self) -> (f32, f32) {
+        let blend_in = self.blend_in.max(0.0);
+        let blend_out = self.blend_out.max(0.0);
+        let total = blend_in + blend_out;
+        match self.playback {
+            Playback::OneShot if total > self.length => {
+                let scale = self.length.max(0.0) / total;
+                (blend_in * scale, blend_out * scale)
+            }
+            _ => (blend_in, blend_out),
+        }
+    }
+
+    pub fn sample(&self, time: f32) -> impl Iterator<Item = (&str, Vec2)> {
+        self.tracks
+            .iter()
+            .filter_map(move |(param, track)| Some((param.as_str(), track.sample(time)?)))
+    }
+}
+
+#[derive(Debug)]
+struct Playing {
+    clip: String,
+    time: f32,
+    weight: f32,
+    stopping: bool,
+}
+
+/// The clips stored for the displayed model, and which of them are playing.
+#[derive(Debug, Default)]
+pub struct AnimationPlayer {
+    clips: Vec<Clip>,
+    playing: Vec<Playing>,
+    // A clip held at a fixed time, while it is being edited.
+    preview: Option<(String, f32)>,
+}
+
+impl Stored for AnimationPlayer {
+    const FILE: &'static str = "animations.json";
+    const NAME: &'static str = "animations";
+    type Data = Vec<Clip>;
+
+    fn from_stored(clips: Vec<Clip>) -> Self {
+        Self {
+            clips,
+            ..Self::default()
+        }
+    }
+
+    fn stored(&self) -> &Vec<Clip> {
+        &self.clips
+    }
+}
+
+impl AnimationPlayer {
+    pub fn clips(&self) -> &[Clip] {
+        &self.clips
+    }
+
+    pub fn clips_mut(&mut self) -> &mut Vec<Clip> {
+        &mut self.clips
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&Clip> {
+        self.clips.iter().find(|x| x.name == name)
+    }
+
+    /// Plays a clip from the start, restarting it if it is already playing.
+    pub fn play(&mut self, name: &str) {
+        if let Some(playing) = self.playing.iter_mut().find(|x| x.clip == name) {
+            playing.time = 0.0;
+            playing.stopping = false;
+        } else if self.clip(name).is_some() {
+            self.playing.push(Playing {
+                clip: name.to_owned(),
+                time: 0.0,
+                weight: 0.0,
+                stopping: false,
+            });
+        }
+    }
+
+    /// Blends a clip out, letting it finish over its blend out time.
+    pub fn stop(&mut self, name: &str) {
+        for playing in self.playing.iter_mut().filter(|x| x.clip == name) {
+            playing.stopping = true;
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        for playing in &mut self.playing {
+            playing.stopping = true;
+        }
+    }
+
+    pub fn is_playing(&self, name: &str) -> bool {
+        self.playing.iter().any(|x| x.clip == name && !x.stopping)
+    }
+
+    /// Progress of a playing clip, from 0 to 1.
+    pub fn progress(&self, name: &str) -> Option<f32> {
+        let playing = self.playing.iter().find(|x| x.clip == name)?;
+        let clip = self.clip(name)?;
+        Some((playing.time / clip.length.max(f32::EPSILON)).clamp(0.0, 1.0))
+    }
+
+    /// Shows a clip at a fixed time instead of normal playback, or returns
+    /// to normal playback if `None`.
+    pub fn set_preview(&mut self, preview: Option<(String, f32)>) {
+        self.preview = preview;
+    }
+
+    /// Advances playback and writes the animated parameters to the mixer.
+    pub fn update(&mut self, dt: f32, mixer: &mut ParamMixer) {
+        mixer.clear(LayerId::Animation);
+
+        if let Some((name, time)) = &self.preview {
+            if let Some(clip) = self.clip(name) {
+                for (param, value) in clip.sample(*time) {
+                    mixer.set(LayerId::Animation, param, value);
+                }
+            }
+            return;
+        }
+
+        let clips = &self.clips;
+        let mut mixed: BTreeMap<&str, (Vec2, f32)> = BTreeMap::new();
+
+        self.playing.retain_mut(|playing| {
+            let Some(clip) = clips.iter().find(|x| x.name == playing.clip) else {
+                return false;
+            };
+
+            let length = clip.length.max(f32::EPSILON);
+            let (blend_in, blend_out) = clip.blend_times();
+            playing.time += dt;
+            match clip.playback {
+                Playback::Loop => playing.time %= length,
+                Playback::OneShot => {
+                    // Start blending out early enough to be done by the end.
+                    if playing.time >= length - blend_out {
+                        playing.stopping = true;
+                    }
+                    playing.time = playing.time.min(length);
+                }
+            }
+
+            playing.weight = if playing.stopping {
+                step_towards(playing.weight, 0.0, dt, blend_out)
+            } else {
+                step_towards(playing.weight, 1.0, dt, blend_in)
+            };
+            if playing.stopping && playing.weight <= 0.0 {
+                return false;
+            }
+
+            // Later clips are blended over earlier ones.
+            for (param, value) in clip.sample(playing.time) {
+                let entry = mixed.entry(param).or_insert((value, 0.0));
+                entry.0 = entry.0.lerp(value, playing.weight);
+                entry.1 += (1.0 - entry.1) * playing.weight;
+            }
+
+            true
+        });
+
+        for (param, (value, weight)) in mixed {
+            mixer.set_weighted(LayerId::Animation, param, value, weight);
+        }
+    }
+}
+
+/// Moves `current` towards `target`, taking `time` seconds to go from 0 to 1.
+pub fn step_towards(current: f32, target: f32, dt: f32, time: f32) -> f32 {
+    if time <= 0.0 {
+        target
+    } else if current < target {
+        (current + dt / time).min(target)
+    } else {
+        (current - dt / time).max(target)
+    }
+}
@@ -0,0 +1,158 @@
+//! The models that have been added, remembered across restarts so they don't
+//! have to be found again every time.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::{self, StorageError};
+
+const LIBRARY_FILE: &str = "library.json";
+/// A small render of each model, kept alongside the model's other data.
+pub const THUMBNAIL_FILE: &str = "thumbnail.png";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntrySettings {
+    /// Loads the model on startup, even if it wasn't the last shown, so
+    /// switching to it is instant.
+    pub preload: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub path: PathBuf,
+    /// The puppet's own name, or its file name if it doesn't have one.
+    pub name: String,
+    /// Seconds since the Unix epoch when the model was last shown.
+    pub last_used: Option<u64>,
+    #[serde(default)]
+    pub settings: EntrySettings,
+    /// Whether the file was missing when last checked.
+    #[serde(skip)]
+    pub missing: bool,
+}
+
+impl LibraryEntry {
+    /// The file name the model's stored data is keyed by.
+    pub fn file_name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    pub fn thumbnail_path(&self) -> Result<PathBuf, StorageError> {
+        storage::model_file(self.file_name(), THUMBNAIL_FILE)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Library {
+    entries: Vec<LibraryEntry>,
+    last_shown: Option<PathBuf>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+impl Library {
+    /// Loads the stored library, checking which of its files still exist.
+    pub fn load() -> Result<Library, StorageError> {
+        let mut library: Library =
+            storage::load(&storage::app_file(LIBRARY_FILE)?)?.unwrap_or_default();
+        for entry in &mut library.entries {
+            entry.missing = !entry.path.is_file();
+        }
+        Ok(library)
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        storage::save(&storage::app_file(LIBRARY_FILE)?, self)
+    }
+
+    pub fn entries(&self) -> &[LibraryEntry] {
+        &self.entries
+    }
+
+    pub fn entry_mut(&mut self, path: &Path) -> Option<&mut LibraryEntry> {
+        self.entries.iter_mut().find(|x| x.path == path)
+    }
+
+    /// Finds an entry by the file name used to refer to models in actions.
+    pub fn find_by_file_name(&self, name: &str) -> Option<&LibraryEntry> {
+        self.entries
+            .iter()
+            .find(|x| x.file_name().to_string_lossy() == name)
+    }
+
+    /// Another model whose stored data would be mixed up with that of the
+    /// model at `path`, as they share a file name.
+    pub fn conflicting(&self, path: &Path) -> Option<&LibraryEntry> {
+        let key = storage::model_key(path.file_name().unwrap_or(path.as_os_str()));
+        self.entries
+            .iter()
+            .find(|x| x.path != path && storage::model_key(x.file_name()) == key)
+    }
+
+    /// Adds a model, or updates its name if it's already in the library.
+    pub fn add(&mut self, path: PathBuf, name: String) {
+        if let Some(entry) = self.entry_mut(&path) {
+            entry.name = name;
+            entry.missing = false;
+            return;
+        }
+
+        self.entries.push(LibraryEntry {
+            path,
+            name,
+            last_used: None,
+            settings: EntrySettings::default(),
+            missing: false,
+        });
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|x| x.path != path);
+        if self.last_shown.as_deref() == Some(path) {
+            self.last_shown = None;
+        }
+    }
+
+    /// The model shown when the app was last used, to show again on startup.
+    pub fn last_shown(&self) -> Option<&Path> {
+        self.last_shown.as_deref()
+    }
+
+    /// Records that a model is now shown, or that none is.
+    pub fn set_shown(&mut self, path: Option<&Path>) {
+        if let Some(entry) = path.and_then(|x| self.entry_mut(x)) {
+            entry.last_used = Some(now());
+        }
+        self.last_shown = path.map(Path::to_owned);
+    }
+}
+
+/// Describes how long ago a `last_used` time was, such as "3 days ago".
+pub fn describe_last_used(last_used: Option<u64>) -> String {
+    let Some(time) = last_used else {
+        return "never shown".to_owned();
+    };
+
+    let elapsed = now().saturating_sub(time);
+    let (amount, unit) = match elapsed {
+        0..=59 => return "just now".to_owned(),
+        60..=3599 => (elapsed / 60, "minute"),
+        3600..=86399 => (elapsed / 3600, "hour"),
+        _ => (elapsed / 86400, "day"),
+    };
+
+    if amount == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", amount, unit)
+    }
+}
@@ -1,8 +1,216 @@
-use self::model::Models;
+use std::{
+    mem::{replace, take},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
 
-mod model;
+use crate::{action::ActionRegistry, displayed_model::DisplayedModel};
 
-#[derive(Debug, Default)]
+use self::{
+    model::{ModelData, Models, PuppetKind},
+    stage::{Guest, Stage},
+};
+
+pub mod library;
+pub mod model;
+pub mod stage;
+pub mod storage;
+
+/// Everything shared between the GUI and the render loop: the model store,
+/// the model being displayed, what else is on stage and the action queue.
 pub struct AppState {
-    models: Models,
+    pub displayed: DisplayedModel,
+    pub actions: ActionRegistry,
+    models: Mutex<Models>,
+    stage: Mutex<Stage>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            displayed: DisplayedModel::default(),
+            actions: ActionRegistry::new(),
+            models: Mutex::new(Models::new()),
+            stage: Mutex::new(Stage::default()),
+        }
+    }
+
+    pub fn models(&self) -> MutexGuard<'_, Models> {
+        self.models.lock().unwrap()
+    }
+
+    /// The stage layout. When both are needed, the models have to be locked
+    /// first.
+    pub fn stage(&self) -> MutexGuard<'_, Stage> {
+        self.stage.lock().unwrap()
+    }
+
+    /// Puts a model on stage alongside the shown one, loading it if it
+    /// hasn't been.
+    pub fn add_guest(&self, path: PathBuf) {
+        let mut models = self.models();
+        if models.shown() == Some(&path) {
+            return;
+        }
+        if !models.is_loaded(&path) {
+            models.load(path.clone());
+        }
+        self.stage().add_guest(path);
+    }
+
+    /// Swaps a guest with the shown model, so its settings can be edited.
+    /// They trade places on stage.
+    pub fn focus_guest(&self, ind: usize) {
+        let mut models = self.models();
+        let Some(guest) = self.stage().guests.get(ind).cloned() else {
+            return;
+        };
+        let previous = models.shown().map(Path::to_owned);
+        if !self.show(&mut models, Some(&guest.path)) {
+            return;
+        }
+
+        let mut stage = self.stage();
+        let transform = replace(&mut stage.transform, guest.transform);
+        if let Some(path) = previous {
+            let ind = ind.min(stage.guests.len());
+            stage.guests.insert(
+                ind,
+                Guest {
+                    path,
+                    transform,
+                    tracker: guest.tracker,
+                    tracker_error: None,
+                },
+            );
+        }
+    }
+
+    /// Takes in models that finished loading, showing the one waiting to be
+    /// shown if it's among them, and reloads those whose files changed.
+    /// Called once a frame.
+    pub fn update(&self) {
+        let mut models = self.models();
+        models.watch();
+        let received = models.receive(&mut self.displayed.bindings.lock().unwrap());
+
+        if received.shown_reloaded {
+            let shown = models.shown().and_then(|x| models.puppet(x));
+            if let Some(puppet) = shown {
+                self.display(puppet.data().clone(), puppet.kind().clone());
+            }
+        }
+        if let Some(path) = received.show {
+            self.show(&mut models, Some(&path));
+        }
+    }
+
+    /// Loads a model from a file given from outside the app, like one
+    /// dropped on the window, adding it to the library once it's loaded. With
+    /// `show`, it's shown then too.
+    pub fn open(&self, path: PathBuf, show: bool) {
+        let mut models = self.models();
+        if !models.is_loaded(&path) {
+            if show {
+                models.load_then_show(path);
+            } else {
+                models.load(path);
+            }
+        } else if show {
+            self.show(&mut models, Some(&path));
+        }
+    }
+
+    /// Shows a model from the library, loading it first if it hasn't been.
+    pub fn show_model(&self, path: &Path) {
+        self.show(&mut self.models(), Some(path));
+    }
+
+    /// Shows the library model with the given file name.
+    pub fn show_named(&self, name: &str) {
+        let mut models = self.models();
+        if let Some(entry) = models.library().find_by_file_name(name) {
+            let path = entry.path.clone();
+            self.show(&mut models, Some(&path));
+        }
+    }
+
+    /// Shows the model `offset` places from the shown one in the library,
+    /// wrapping around at either end. Models whose files are missing are
+    /// skipped.
+    pub fn show_relative(&self, offset: isize) {
+        let mut models = self.models();
+        let available: Vec<_> = models
+            .library()
+            .entries()
+            .iter()
+            .filter(|x| !x.missing)
+            .map(|x| x.path.clone())
+            .collect();
+        if available.is_empty() {
+            return;
+        }
+
+        let next = match models
+            .shown()
+            .and_then(|shown| available.iter().position(|x| x == shown))
+        {
+            Some(ind) => (ind as isize + offset).rem_euclid(available.len() as isize) as usize,
+            None => 0,
+        };
+        self.show(&mut models, Some(&available[next]));
+    }
+
+    pub fn hide_model(&self) {
+        self.show(&mut self.models(), None);
+    }
+
+    /// Shows a model, or none, returning whether it's shown now rather than
+    /// once it loads.
+    fn show(&self, models: &mut Models, path: Option<&Path>) -> bool {
+        let next = match path {
+            Some(path) => match models.puppet(path) {
+                Some(puppet) => Some((
+                    puppet.data().clone(),
+                    puppet.kind().clone(),
+                    puppet.bindings().clone(),
+                )),
+                None => {
+                    models.load_and_show(path);
+                    return false;
+                }
+            },
+            None => None,
+        };
+
+        // The outgoing model keeps its bindings for when it's shown again.
+        let mut bindings = self.displayed.bindings.lock().unwrap();
+        models.store_bindings(take(&mut bindings));
+
+        match next {
+            Some((data, kind, next_bindings)) => {
+                *bindings = next_bindings;
+                drop(bindings);
+                self.display(data, kind);
+            }
+            None => {
+                drop(bindings);
+                self.displayed.swap_model(None);
+            }
+        }
+        models.set_shown(path);
+
+        // A model can't be on stage twice.
+        if let Some(path) = path {
+            self.stage().remove_guest(path);
+        }
+        true
+    }
+
+    fn display(&self, data: Arc<ModelData>, kind: PuppetKind) {
+        match kind {
+            PuppetKind::Inochi2D(model) => self.displayed.swap_model(Some((data, model))),
+            PuppetKind::Static(puppet) => self.displayed.swap_static((data, puppet)),
+        }
+    }
 }
@@ -1,23 +1,150 @@
-use std::{ffi::OsString, fs::File, io, path::Path};
+mod stored;
 
-use inox2d::{formats::inp::ParseInpError, model::Model, puppet::Puppet};
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufReader},
+    mem::discriminant,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::spawn,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use inox2d::{formats::inp::ParseInpError, model::Model};
 use thiserror::Error;
 
+use crate::{
+    static_puppet::{self, StaticError, StaticPuppet},
+    tracker::{ParamBinding, ParamBindings},
+};
+
+use super::library::Library;
+
+pub use stored::{ModelData, Stored};
+
+/// How often loaded models' files are checked for changes. A change is only
+/// picked up once the file has stayed the same for a whole check, so a
+/// model that's still being exported isn't read half written.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A loaded model, along with everything set up for it this session.
 #[derive(Debug)]
 pub struct InternalPuppet {
-    folder: OsString,
+    path: PathBuf,
     kind: PuppetKind,
+    bindings: ParamBindings,
+    // When the file was last modified as of loading it.
+    modified: Option<SystemTime>,
+    // Everything kept for the model across sessions.
+    data: Arc<ModelData>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum PuppetKind {
-    Inochi2D(Puppet),
-    Static,
+    Inochi2D(Model),
+    Static(StaticPuppet),
 }
 
-#[derive(Debug, Default)]
-pub struct Models {
-    loaded_models: Vec<InternalPuppet>,
+impl PuppetKind {
+    /// Loads a puppet of whichever kind the file's extension says it is.
+    fn load(path: &Path) -> Result<PuppetKind, PuppetError> {
+        if static_puppet::is_static_file(path) {
+            Ok(PuppetKind::Static(StaticPuppet::load(path)?))
+        } else {
+            let (_, model) = load_i2d_puppet_from_file(path)?;
+            Ok(PuppetKind::Inochi2D(model))
+        }
+    }
+
+    /// The puppet's own name, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            PuppetKind::Inochi2D(model) => model.puppet.meta.name.as_deref(),
+            PuppetKind::Static(puppet) => puppet.name.as_deref(),
+        }
+    }
+}
+
+impl InternalPuppet {
+    fn new(
+        path: PathBuf,
+        kind: PuppetKind,
+        modified: Option<SystemTime>,
+        data: Arc<ModelData>,
+    ) -> Self {
+        // Static puppets have no parameters to bind.
+        let mut bindings = ParamBindings::new();
+        if let PuppetKind::Inochi2D(model) = &kind {
+            for (k, v) in &model.puppet.parameters {
+                if v.is_vec2 {
+                    bindings.insert(k.to_owned(), ParamBinding::TwoDim(None));
+                } else {
+                    bindings.insert(k.to_owned(), ParamBinding::OneDim(None));
+                }
+            }
+        }
+        // Saved bindings are taken up for the parameters the model still has.
+        carry_bindings(data.saved_bindings(), &mut bindings);
+
+        Self {
+            path,
+            kind,
+            bindings,
+            modified,
+            data,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &PuppetKind {
+        &self.kind
+    }
+
+    pub fn bindings(&self) -> &ParamBindings {
+        &self.bindings
+    }
+
+    pub fn data(&self) -> &Arc<ModelData> {
+        &self.data
+    }
+
+    /// When the model's file was last modified as of loading it. This
+    /// changes whenever the model is reloaded.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+/// Carries bindings over to a reloaded model for the parameters it still
+/// has, returning the names of those added and removed. A parameter that
+/// changed between one and two dimensions counts as both.
+fn carry_bindings(old: &ParamBindings, new: &mut ParamBindings) -> (Vec<String>, Vec<String>) {
+    let mut added = Vec::new();
+    let mut removed: Vec<_> = old
+        .keys()
+        .filter(|x| !new.contains_key(*x))
+        .cloned()
+        .collect();
+
+    for (name, binding) in new.iter_mut() {
+        match old.get(name) {
+            Some(old) if discriminant(old) == discriminant(binding) => *binding = old.clone(),
+            Some(_) => {
+                added.push(name.clone());
+                removed.push(name.clone());
+            }
+            None => added.push(name.clone()),
+        }
+    }
+    removed.sort();
+
+    (added, removed)
 }
 
 #[derive(Error, Debug)]
@@ -33,8 +160,8 @@ pub fn load_i2d_puppet_from_file<P: AsRef<Path>>(
 ) -> Result<(OsString, Model), LoadError<ParseInpError>> {
     let path = path.as_ref();
 
-    let model = inox2d::formats::inp::parse_inp(File::open(path)?)
-        .map_err(|e| LoadError::InvalidFile(e))?;
+    let model = inox2d::formats::inp::parse_inp(BufReader::new(File::open(path)?))
+        .map_err(LoadError::InvalidFile)?;
 
     let file_name = path
         .file_name()
@@ -43,3 +170,477 @@ pub fn load_i2d_puppet_from_file<P: AsRef<Path>>(
 
     Ok((file_name, model))
 }
+
+#[derive(Error, Debug)]
+pub enum PuppetError {
+    #[error("could not load Inochi2D puppet")]
+    Inochi2D(#[from] LoadError<ParseInpError>),
+    #[error("could not load static puppet")]
+    Static(#[from] StaticError),
+}
+
+impl PuppetError {
+    /// Whether the error came from a file that doesn't exist.
+    fn is_not_found(&self) -> bool {
+        match self {
+            PuppetError::Inochi2D(LoadError::FailedToRead(e)) => {
+                e.kind() == io::ErrorKind::NotFound
+            }
+            PuppetError::Inochi2D(_) => false,
+            PuppetError::Static(e) => e.is_not_found(),
+        }
+    }
+}
+
+/// Describes an error along with everything that caused it.
+fn describe_error(error: &dyn Error) -> String {
+    let mut out = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        out.push_str(": ");
+        out.push_str(&e.to_string());
+        source = e.source();
+    }
+    out
+}
+
+/// Whether a file looks like a model that can be loaded, going by its
+/// extension.
+pub fn is_model_file(path: &Path) -> bool {
+    static_puppet::is_static_file(path)
+        || path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("inp"))
+}
+
+type Loaded = (PathBuf, Option<SystemTime>, Result<PuppetKind, PuppetError>);
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+/// A model that was reloaded after its file changed, and how its parameters
+/// changed.
+#[derive(Debug)]
+pub struct Reload {
+    pub path: PathBuf,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// What came of the loads that finished since last asked.
+#[derive(Default)]
+pub(super) struct Received {
+    /// The model waiting to be shown, if it's loaded now.
+    pub show: Option<PathBuf>,
+    /// Whether the shown model was reloaded.
+    pub shown_reloaded: bool,
+}
+
+/// Every model known to the app: the library of added models, those loaded
+/// so far, and which is shown.
+#[derive(Debug)]
+pub struct Models {
+    library: Library,
+    loaded: Vec<InternalPuppet>,
+    shown: Option<PathBuf>,
+    // Models being loaded, and when they started. A load that's cancelled
+    // still runs to the end, but its result is thrown away.
+    loading: Vec<(PathBuf, Instant)>,
+    // A model waiting to be shown as soon as it finishes loading.
+    show_when_loaded: Option<PathBuf>,
+    // Files that failed to load, and why.
+    errors: Vec<(PathBuf, String)>,
+    // Loaded models being read again after their files changed.
+    reloading: Vec<PathBuf>,
+    reloads: Vec<Reload>,
+    // Files seen changed at the last check, and their modified times then.
+    changed: Vec<(PathBuf, SystemTime)>,
+    last_checked: Instant,
+    sender: Sender<Loaded>,
+    receiver: Receiver<Loaded>,
+}
+
+impl Models {
+    /// Loads the library, along with the model last shown and any asked to
+    /// be preloaded. The rest wait until they're shown.
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded();
+        let library = Library::load().unwrap_or_else(|e| {
+            eprintln!("Failed to load model library: {}", e);
+            Library::default()
+        });
+
+        let mut out = Self {
+            library,
+            loaded: Vec::new(),
+            shown: None,
+            loading: Vec::new(),
+            show_when_loaded: None,
+            errors: Vec::new(),
+            reloading: Vec::new(),
+            reloads: Vec::new(),
+            changed: Vec::new(),
+            last_checked: Instant::now(),
+            sender,
+            receiver,
+        };
+
+        let last_shown = out.library.last_shown().map(Path::to_owned);
+        let startup: Vec<_> = out
+            .library
+            .entries()
+            .iter()
+            .filter(|x| !x.missing)
+            .filter(|x| x.settings.preload || Some(&x.path) == last_shown.as_ref())
+            .map(|x| x.path.clone())
+            .collect();
+        for path in startup {
+            out.load(path);
+        }
+        out.show_when_loaded = last_shown;
+
+        out
+    }
+
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    fn save_library(&self) {
+        if let Err(e) = self.library.save() {
+            eprintln!("Failed to save model library: {}", e);
+        }
+    }
+
+    /// The file names of the models in the library, in the order they are
+    /// listed.
+    pub fn model_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.library
+            .entries()
+            .iter()
+            .map(|x| x.file_name().to_string_lossy().into_owned())
+    }
+
+    pub fn puppet(&self, path: &Path) -> Option<&InternalPuppet> {
+        self.loaded.iter().find(|x| x.path == path)
+    }
+
+    pub fn is_loaded(&self, path: &Path) -> bool {
+        self.puppet(path).is_some()
+    }
+
+    /// The path of the shown model, if any.
+    pub fn shown(&self) -> Option<&Path> {
+        self.shown.as_deref()
+    }
+
+    pub fn loading(&self) -> &[(PathBuf, Instant)] {
+        &self.loading
+    }
+
+    pub fn errors(&self) -> &[(PathBuf, String)] {
+        &self.errors
+    }
+
+    /// Reports a problem with a model file, in place of any earlier one.
+    pub fn report_error(&mut self, path: PathBuf, error: String) {
+        self.errors.retain(|x| x.0 != path);
+        self.errors.push((path, error));
+    }
+
+    pub fn dismiss_error(&mut self, ind: usize) {
+        if ind < self.errors.len() {
+            self.errors.remove(ind);
+        }
+    }
+
+    /// Models reloaded after their files changed.
+    pub fn reloads(&self) -> &[Reload] {
+        &self.reloads
+    }
+
+    pub fn dismiss_reload(&mut self, ind: usize) {
+        if ind < self.reloads.len() {
+            self.reloads.remove(ind);
+        }
+    }
+
+    pub fn set_preload(&mut self, path: &Path, preload: bool) {
+        if let Some(entry) = self.library.entry_mut(path) {
+            entry.settings.preload = preload;
+        }
+        self.save_library();
+    }
+
+    /// Removes a model from the library, unloading it. A shown model stays
+    /// shown until something else is.
+    pub fn remove(&mut self, path: &Path) {
+        self.library.remove(path);
+        self.loaded.retain(|x| x.path != path);
+        self.reloads.retain(|x| x.path != path);
+        self.save_library();
+    }
+
+    /// Loads a model in the background, adding it to the library once it
+    /// has loaded.
+    pub fn load(&mut self, path: PathBuf) {
+        if self.loading.iter().any(|x| x.0 == path) {
+            return;
+        }
+        self.loading.push((path.clone(), Instant::now()));
+        self.spawn_load(path);
+    }
+
+    fn spawn_load(&self, path: PathBuf) {
+        let sender = self.sender.clone();
+        spawn(move || {
+            // Read before the file, so a change made while it's being read
+            // is picked up later.
+            let modified = modified_time(&path);
+            let puppet = PuppetKind::load(&path);
+            let _ = sender.send((path, modified, puppet));
+        });
+    }
+
+    /// Reloads any loaded model whose file has changed and since settled.
+    /// Called once a frame, but only looks every `WATCH_INTERVAL`.
+    pub(super) fn watch(&mut self) {
+        if self.last_checked.elapsed() < WATCH_INTERVAL {
+            return;
+        }
+        self.last_checked = Instant::now();
+
+        let mut settled = Vec::new();
+        let mut changed = Vec::new();
+        for puppet in &self.loaded {
+            let Some(modified) = modified_time(&puppet.path) else {
+                continue;
+            };
+            if Some(modified) == puppet.modified {
+                continue;
+            }
+
+            let seen = self.changed.iter().find(|x| x.0 == puppet.path);
+            if seen.is_some_and(|x| x.1 == modified) {
+                settled.push(puppet.path.clone());
+            } else {
+                changed.push((puppet.path.clone(), modified));
+            }
+        }
+        self.changed = changed;
+
+        for path in settled {
+            if self.reloading.contains(&path) || self.loading.iter().any(|x| x.0 == path) {
+                continue;
+            }
+            self.reloading.push(path.clone());
+            self.spawn_load(path);
+        }
+    }
+
+    /// Loads a model, showing it once it's loaded, unless its file has gone
+    /// missing.
+    pub(super) fn load_and_show(&mut self, path: &Path) {
+        let Some(entry) = self.library.entry_mut(path) else {
+            return;
+        };
+        entry.missing = !path.is_file();
+        if !entry.missing {
+            self.load_then_show(path.to_owned());
+        }
+    }
+
+    /// Loads a model, showing it once it's loaded in place of any other
+    /// waiting to be shown.
+    pub(super) fn load_then_show(&mut self, path: PathBuf) {
+        self.show_when_loaded = Some(path.clone());
+        self.load(path);
+    }
+
+    /// Stops waiting for a model to load.
+    pub fn cancel(&mut self, path: &Path) {
+        self.loading.retain(|x| x.0 != path);
+        if self.show_when_loaded.as_deref() == Some(path) {
+            self.show_when_loaded = None;
+        }
+    }
+
+    /// Takes in models that finished loading. `shown_bindings` are those of
+    /// the shown model, which are carried over if it's reloaded or loaded
+    /// again.
+    pub(super) fn receive(&mut self, shown_bindings: &mut ParamBindings) -> Received {
+        let received: Vec<_> = self.receiver.try_iter().collect();
+        if received.is_empty() {
+            return Received::default();
+        }
+
+        let mut out = Received::default();
+        for (path, modified, result) in received {
+            if let Some(ind) = self.reloading.iter().position(|x| *x == path) {
+                self.reloading.swap_remove(ind);
+                let shown = self.shown.as_ref() == Some(&path);
+                let bindings = shown.then_some(&mut *shown_bindings);
+                if self.finish_reload(path, modified, result, bindings) && shown {
+                    out.shown_reloaded = true;
+                }
+                continue;
+            }
+
+            // Anything no longer waited for was cancelled.
+            let Some(ind) = self.loading.iter().position(|x| x.0 == path) else {
+                continue;
+            };
+            self.loading.swap_remove(ind);
+
+            let kind = match result {
+                Ok(kind) => kind,
+                Err(e) => {
+                    if let Some(entry) = self.library.entry_mut(&path) {
+                        entry.missing = e.is_not_found();
+                    }
+                    if self.show_when_loaded.as_ref() == Some(&path) {
+                        self.show_when_loaded = None;
+                    }
+
+                    self.errors.retain(|x| x.0 != path);
+                    self.errors.push((path, describe_error(&e)));
+                    continue;
+                }
+            };
+
+            self.errors.retain(|x| x.0 != path);
+            // Settings are stored by file name, and so are actions showing a
+            // model, so a second model by the same name isn't taken in.
+            if let Some(other) = self.library.conflicting(&path) {
+                let error = format!(
+                    "A model with the same file name is already in the library, at {}. \
+                    Rename one of them to use both.",
+                    other.path.display()
+                );
+                if self.show_when_loaded.as_ref() == Some(&path) {
+                    self.show_when_loaded = None;
+                }
+                self.errors.push((path, error));
+                continue;
+            }
+
+            let name = kind.name().map(str::to_owned).unwrap_or_else(|| {
+                path.file_stem()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            self.library.add(path.clone(), name);
+
+            // A model loaded again keeps what's already set up for it.
+            let existing = self.loaded.iter().find(|x| x.path == path);
+            let data = match existing {
+                Some(loaded) => loaded.data.clone(),
+                None => {
+                    let file_name = path.file_name().unwrap_or(path.as_os_str());
+                    let (data, errors) = ModelData::load(file_name.to_owned());
+                    for error in errors {
+                        self.errors.push((path.clone(), error));
+                    }
+                    Arc::new(data)
+                }
+            };
+
+            let mut puppet = InternalPuppet::new(path.clone(), kind, modified, data);
+            if let Some(loaded) = self.loaded.iter_mut().find(|x| x.path == path) {
+                // The shown model's bindings are only up to date outside the
+                // store, as when reloading.
+                if self.shown.as_ref() == Some(&path) {
+                    carry_bindings(shown_bindings, &mut puppet.bindings);
+                    *shown_bindings = puppet.bindings.clone();
+                    out.shown_reloaded = true;
+                } else {
+                    carry_bindings(&loaded.bindings, &mut puppet.bindings);
+                }
+                *loaded = puppet;
+            } else {
+                self.loaded.push(puppet);
+            }
+
+            if self.show_when_loaded.as_ref() == Some(&path) {
+                self.show_when_loaded = None;
+                out.show = Some(path);
+            }
+        }
+        self.save_library();
+
+        out
+    }
+
+    /// Swaps in a reloaded model, keeping the bindings of the parameters it
+    /// still has. The shown model's are passed in, as it has the only up to
+    /// date copy. Returns whether the model was swapped.
+    fn finish_reload(
+        &mut self,
+        path: PathBuf,
+        modified: Option<SystemTime>,
+        result: Result<PuppetKind, PuppetError>,
+        shown_bindings: Option<&mut ParamBindings>,
+    ) -> bool {
+        // The model may have been removed while it was being read.
+        let Some(puppet) = self.loaded.iter_mut().find(|x| x.path == path) else {
+            return false;
+        };
+
+        let kind = match result {
+            Ok(kind) => kind,
+            Err(e) => {
+                // The old model stays, and the file isn't read again until
+                // it changes again.
+                puppet.modified = modified;
+                self.errors.retain(|x| x.0 != path);
+                self.errors.push((path, describe_error(&e)));
+                return false;
+            }
+        };
+
+        // What's stored for the model carries over as it is.
+        let data = puppet.data.clone();
+        let mut next = InternalPuppet::new(path.clone(), kind, modified, data);
+        let (added, removed) = match shown_bindings {
+            Some(bindings) => {
+                let changes = carry_bindings(bindings, &mut next.bindings);
+                *bindings = next.bindings.clone();
+                changes
+            }
+            None => carry_bindings(&puppet.bindings, &mut next.bindings),
+        };
+        *puppet = next;
+
+        self.errors.retain(|x| x.0 != path);
+        self.reloads.retain(|x| x.path != path);
+        self.reloads.push(Reload {
+            path,
+            added,
+            removed,
+        });
+        true
+    }
+
+    /// Keeps the bindings edited while a model was shown, for when it's
+    /// shown again, and saves them if they changed.
+    pub(super) fn store_bindings(&mut self, bindings: ParamBindings) {
+        let shown = self.shown.as_deref();
+        if let Some(puppet) = self.loaded.iter_mut().find(|x| Some(x.path()) == shown) {
+            if puppet.bindings != bindings {
+                if let Err(e) = puppet.data.save(&bindings) {
+                    let error = format!("Could not save bindings: {}", describe_error(&e));
+                    self.errors.push((puppet.path.clone(), error));
+                }
+            }
+            puppet.bindings = bindings;
+        }
+    }
+
+    pub(super) fn set_shown(&mut self, path: Option<&Path>) {
+        self.shown = path.map(Path::to_owned);
+        self.library.set_shown(path);
+        self.save_library();
+    }
+}
@@ -0,0 +1,108 @@
+//! What's kept for each model across sessions, such as its animations and
+//! expressions, each in a file of its own among the model's stored data.
+
+use std::{
+    ffi::OsString,
+    sync::{Arc, Mutex},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    animation::AnimationPlayer,
+    app::storage::{self, StorageError},
+    expression::ExpressionPlayer,
+    outfit::OutfitPlayer,
+    physics::Physics,
+    pose::PosePlayer,
+    static_puppet::StaticOverride,
+    tracker::{ParamBindings, Triggers},
+};
+
+use super::describe_error;
+
+/// Something kept for each model, which plays or applies what's stored.
+pub trait Stored: Default {
+    /// The file it's kept in.
+    const FILE: &'static str;
+    /// What it's called in errors.
+    const NAME: &'static str;
+    type Data: Serialize + DeserializeOwned + Default;
+
+    /// Sets up from what was stored, with nothing playing.
+    fn from_stored(data: Self::Data) -> Self;
+
+    /// What's written to the file.
+    fn stored(&self) -> &Self::Data;
+}
+
+/// Everything kept for a model, loaded along with it. The default is what's
+/// used while no model is shown, and is never saved.
+#[derive(Debug, Default)]
+pub struct ModelData {
+    // The file name everything is keyed by.
+    name: Option<Arc<OsString>>,
+    // The bindings as saved when the model was loaded. Those edited since
+    // are kept with the loaded model.
+    bindings: ParamBindings,
+    pub animations: Mutex<AnimationPlayer>,
+    pub expressions: Mutex<ExpressionPlayer>,
+    pub triggers: Mutex<Triggers>,
+    pub poses: Mutex<PosePlayer>,
+    pub physics: Mutex<Physics>,
+    pub outfits: Mutex<OutfitPlayer>,
+    pub static_settings: Mutex<StaticOverride>,
+}
+
+impl ModelData {
+    /// Loads everything stored for a model. Whatever can't be loaded starts
+    /// out empty, and is described in the errors returned.
+    pub fn load(name: OsString) -> (ModelData, Vec<String>) {
+        let mut errors = Vec::new();
+        let data = ModelData {
+            bindings: load_one(&name, &mut errors),
+            animations: Mutex::new(load_one(&name, &mut errors)),
+            expressions: Mutex::new(load_one(&name, &mut errors)),
+            triggers: Mutex::new(load_one(&name, &mut errors)),
+            poses: Mutex::new(load_one(&name, &mut errors)),
+            physics: Mutex::new(load_one(&name, &mut errors)),
+            outfits: Mutex::new(load_one(&name, &mut errors)),
+            static_settings: Mutex::new(load_one(&name, &mut errors)),
+            name: Some(Arc::new(name)),
+        };
+        (data, errors)
+    }
+
+    /// The file name of the model, which its data is keyed by.
+    pub fn name(&self) -> Option<Arc<OsString>> {
+        self.name.clone()
+    }
+
+    /// The bindings saved for the model, from before it was loaded.
+    pub fn saved_bindings(&self) -> &ParamBindings {
+        &self.bindings
+    }
+
+    pub fn save<T: Stored>(&self, value: &T) -> Result<(), StorageError> {
+        if let Some(name) = &self.name {
+            storage::save(&storage::model_file(name, T::FILE)?, value.stored())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn load_one<T: Stored>(name: &OsString, errors: &mut Vec<String>) -> T {
+    let data = storage::model_file(name, T::FILE).and_then(|path| storage::load(&path));
+    match data {
+        Ok(data) => T::from_stored(data.unwrap_or_default()),
+        Err(e) => {
+            errors.push(format!(
+                "Could not load {}: {}",
+                T::NAME,
+                describe_error(&e)
+            ));
+            T::default()
+        }
+    }
+}
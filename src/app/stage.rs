@@ -0,0 +1,104 @@
+//! Where puppets are placed in the frame. The shown model is always on
+//! stage, and other models from the library can join it as guests.
+
+use std::path::{Path, PathBuf};
+
+use glam::Vec2;
+use inox2d::math::camera::Camera;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    /// Offset from the middle of the stage, in model units.
+    pub position: Vec2,
+    pub scale: f32,
+    /// Rotation in degrees.
+    pub rotation: f32,
+    /// Mirrors the puppet horizontally.
+    pub flip: bool,
+    /// Puppets with a higher order are drawn in front.
+    pub z_order: i32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            scale: 1.0,
+            rotation: 0.0,
+            flip: false,
+            z_order: 0,
+        }
+    }
+}
+
+impl Transform {
+    /// Sets up the camera a puppet is rendered with, so it appears placed on
+    /// stage as seen through the stage camera.
+    pub fn apply(&self, stage: &Camera, camera: &mut Camera) {
+        let scale = self.scale.max(0.01);
+        let flip = if self.flip { -1.0 } else { 1.0 };
+        let rotation = self.rotation.to_radians();
+
+        let offset = Vec2::from_angle(-rotation).rotate(stage.position + self.position) / scale;
+        camera.position = Vec2::new(offset.x * flip, offset.y);
+        camera.rotation = stage.rotation + rotation;
+        camera.scale = stage.scale * Vec2::new(scale * flip, scale);
+    }
+}
+
+/// Where a guest's tracking data comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrackerSource {
+    /// The tracker set up in the tracker settings, shared with the shown
+    /// model.
+    #[default]
+    Shared,
+    /// A tracker of its own, listening for VMC data on a port.
+    Port(u16),
+}
+
+#[derive(Clone, Debug)]
+pub struct Guest {
+    pub path: PathBuf,
+    pub transform: Transform,
+    pub tracker: TrackerSource,
+    /// Why its own tracker isn't listening, as last reported by the
+    /// renderer.
+    pub tracker_error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Stage {
+    /// Where the shown model is placed.
+    pub transform: Transform,
+    pub guests: Vec<Guest>,
+}
+
+impl Stage {
+    pub fn guest(&self, path: &Path) -> Option<&Guest> {
+        self.guests.iter().find(|x| x.path == path)
+    }
+
+    /// Adds a model as a guest, placed a little to the side so it doesn't
+    /// cover the shown model.
+    pub fn add_guest(&mut self, path: PathBuf) {
+        if self.guest(&path).is_some() {
+            return;
+        }
+
+        let side = self.guests.len() as f32 + 1.0;
+        self.guests.push(Guest {
+            path,
+            transform: Transform {
+                position: self.transform.position + Vec2::new(side * 2000.0, 0.0),
+                ..Transform::default()
+            },
+            tracker: TrackerSource::Shared,
+            tracker_error: None,
+        });
+    }
+
+    pub fn remove_guest(&mut self, path: &Path) {
+        self.guests.retain(|x| x.path != path);
+    }
+}
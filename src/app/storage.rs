@@ -0,0 +1,83 @@
+//! Virst's own data, such as per-model animations, kept in the platform's
+//! app data directory.
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("no app data directory is available")]
+    NoDataDir,
+    #[error("could not access stored data")]
+    Io(#[from] io::Error),
+    #[error("stored data is malformed")]
+    Malformed(#[from] serde_json::Error),
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "MahouTechnologies", "Virst").map(|x| x.data_dir().to_owned())
+}
+
+/// The path of a file stored for the app as a whole, such as settings.
+pub fn app_file(file: &str) -> Result<PathBuf, StorageError> {
+    let mut path = data_dir().ok_or(StorageError::NoDataDir)?;
+    path.push(file);
+    Ok(path)
+}
+
+/// What a model's stored data is keyed by, given its file name. Models are
+/// keyed by their file name without the extension, so a re-exported model
+/// keeps its data, which means the library can't hold two models with the
+/// same key. Anything that can't be in a directory name is replaced.
+pub fn model_key(model: &OsStr) -> String {
+    Path::new(model)
+        .file_stem()
+        .unwrap_or(model)
+        .to_string_lossy()
+        .chars()
+        .map(|x| {
+            if x.is_alphanumeric() || "-_ .".contains(x) {
+                x
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The path of a file stored for a model, keyed by the model's file name.
+pub fn model_file(model: &OsStr, file: &str) -> Result<PathBuf, StorageError> {
+    let mut path = data_dir().ok_or(StorageError::NoDataDir)?;
+    path.push("models");
+    path.push(model_key(model));
+    path.push(file);
+    Ok(path)
+}
+
+/// Loads a stored value, returning `None` if nothing has been stored yet.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, StorageError> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write to the side first, so a crash can't leave a half written file.
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(temp, path)?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::f32::consts::TAU;
+
+/// Seconds of audio analysed at once, which is long enough to resolve the
+/// formants of a voice while still following syllables.
+const WINDOW_LENGTH: f32 = 0.032;
+
+/// Approximate first and second formants of each vowel, in Hz, in the order
+/// A, I, U, E, O.
+const FORMANTS: [(f32, f32); 5] = [
+    (800.0, 1200.0),
+    (300.0, 2300.0),
+    (350.0, 1300.0),
+    (500.0, 1900.0),
+    (500.0, 850.0),
+];
+
+/// How far from a vowel's formants, in octaves, a voice can be while still
+/// being mostly that vowel.
+const FORMANT_SPREAD: f32 = 0.35;
+
+/// What was heard in one window of audio.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Levels {
+    /// Loudness in decibels relative to full scale.
+    pub loudness: f32,
+    /// How much the sound resembles each vowel, summing to 1 unless nothing
+    /// was heard.
+    pub vowels: [f32; 5],
+}
+
+/// Splits a stream of mono samples into overlapping windows and estimates
+/// loudness and vowel shape for each.
+pub struct Analyzer {
+    sample_rate: f32,
+    buffer: Vec<f32>,
+    // A Hann window, reducing leakage between nearby frequencies.
+    window: Vec<f32>,
+}
+
+impl Analyzer {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1) as f32;
+        let len = ((sample_rate * WINDOW_LENGTH) as usize).max(16);
+        Self {
+            sample_rate,
+            buffer: Vec::with_capacity(len),
+            window: (0..len)
+                .map(|i| 0.5 - 0.5 * (TAU * i as f32 / len as f32).cos())
+                .collect(),
+        }
+    }
+
+    /// Adds samples, returning the levels of the latest window completed by
+    /// them, if any.
+    pub fn push(&mut self, samples: &[f32]) -> Option<Levels> {
+        let mut out = None;
+        for &sample in samples {
+            self.buffer.push(sample);
+            if self.buffer.len() == self.window.len() {
+                out = Some(self.analyze());
+                // Windows overlap by half, so quick sounds aren't missed.
+                self.buffer.drain(..self.window.len() / 2);
+            }
+        }
+        out
+    }
+
+    fn analyze(&self) -> Levels {
+        let len = self.buffer.len() as f32;
+        let rms = (self.buffer.iter().map(|x| x * x).sum::<f32>() / len).sqrt();
+        let loudness = 20.0 * rms.max(1e-6).log10();
+
+        let windowed: Vec<f32> = self
+            .buffer
+            .iter()
+            .zip(&self.window)
+            .map(|(x, w)| x * w)
+            .collect();
+
+        // Only the range formants fall in is needed, so the bins are computed
+        // individually rather than with a full FFT.
+        let bin_width = self.sample_rate / len;
+        let first = ((200.0 / bin_width).ceil() as usize).max(1);
+        let last = ((3000.0 / bin_width) as usize).min(self.buffer.len() / 2);
+        let spectrum: Vec<(f32, f32)> = (first..=last)
+            .map(|bin| {
+                (
+                    bin as f32 * bin_width,
+                    goertzel(&windowed, bin as f32 / len),
+                )
+            })
+            .collect();
+
+        let (Some(f1), Some(f2)) = (
+            centroid(&spectrum, 200.0, 1000.0),
+            centroid(&spectrum, 1000.0, 3000.0),
+        ) else {
+            return Levels {
+                loudness,
+                vowels: [0.0; 5],
+            };
+        };
+
+        let mut vowels = FORMANTS.map(|(v1, v2)| {
+            let d1 = (f1 / v1).log2() / FORMANT_SPREAD;
+            let d2 = (f2 / v2).log2() / FORMANT_SPREAD;
+            (-(d1 * d1 + d2 * d2) / 2.0).exp()
+        });
+        let total: f32 = vowels.iter().sum();
+        if total > 0.0 {
+            for vowel in &mut vowels {
+                *vowel /= total;
+            }
+        }
+
+        Levels { loudness, vowels }
+    }
+}
+
+/// The power of a single frequency, given as a fraction of the sample rate.
+fn goertzel(samples: &[f32], frequency: f32) -> f32 {
+    let coeff = 2.0 * (TAU * frequency).cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for x in samples {
+        let s = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0)
+}
+
+/// The power weighted mean frequency between `low` and `high`, or `None` if
+/// there's no power there.
+fn centroid(spectrum: &[(f32, f32)], low: f32, high: f32) -> Option<f32> {
+    let (sum, power) = spectrum
+        .iter()
+        .filter(|(freq, _)| (low..high).contains(freq))
+        .fold((0.0, 0.0), |(sum, total), (freq, power)| {
+            (sum + freq * power, total + power)
+        });
+    (power > 1e-9).then_some(sum / power)
+}
@@ -0,0 +1,43 @@
+//! Lip sync inputs, estimating how loud a voice is and which vowel it's
+//! making from PCM audio.
+
+mod analysis;
+mod system;
+mod wav;
+
+pub use system::{AudioError, AudioSettings, AudioSource, AudioSystem};
+pub use wav::WavError;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AudioKind {
+    Volume,
+    A,
+    I,
+    U,
+    E,
+    O,
+}
+
+impl AudioKind {
+    pub const ALL: [AudioKind; 6] = [
+        AudioKind::Volume,
+        AudioKind::A,
+        AudioKind::I,
+        AudioKind::U,
+        AudioKind::E,
+        AudioKind::O,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            AudioKind::Volume => "Voice Volume",
+            AudioKind::A => "Voice A",
+            AudioKind::I => "Voice I",
+            AudioKind::U => "Voice U",
+            AudioKind::E => "Voice E",
+            AudioKind::O => "Voice O",
+        }
+    }
+}
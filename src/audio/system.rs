@@ -0,0 +1,296 @@
+use std::{
+    fs::File,
+    io::{self, ErrorKind, Read},
+    net::{SocketAddr, UdpSocket},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use super::{
+    analysis::{Analyzer, Levels},
+    wav::{Wav, WavError},
+    AudioKind,
+};
+
+/// How long after the last analysed window audio is considered silent.
+const AUDIO_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Where PCM audio is read from. Raw streams are 16 bit little-endian mono
+/// at the given sample rate.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioSource {
+    /// A WAV file, played through once in real time.
+    File(PathBuf),
+    /// Datagrams sent to a local UDP port.
+    Udp(SocketAddr, u32),
+    /// A named pipe, or anything else that can be read like a file.
+    Pipe(PathBuf, u32),
+}
+
+impl AudioSource {
+    pub fn name(&self) -> String {
+        match self {
+            AudioSource::File(path) => format!("WAV file {}", path.display()),
+            AudioSource::Udp(addr, rate) => format!("UDP {} ({} Hz)", addr, rate),
+            AudioSource::Pipe(path, rate) => format!("Pipe {} ({} Hz)", path.display(), rate),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AudioError {
+    #[error("audio is already connected")]
+    AlreadyConnected,
+    #[error("could not open the audio source")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Wav(#[from] WavError),
+}
+
+#[derive(Clone, Debug)]
+pub struct AudioSettings {
+    /// Decibels added to the input before it's measured.
+    pub gain: f32,
+    /// Loudness, in decibels, treated as silence.
+    pub floor: f32,
+    /// The fraction of the previous values kept every 1/60th of a second.
+    pub smoothing: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            gain: 0.0,
+            floor: -50.0,
+            smoothing: 0.5,
+        }
+    }
+}
+
+/// The most recently analysed window, and when it was analysed.
+type Latest = Mutex<Option<(Levels, Instant)>>;
+
+pub struct AudioSystem {
+    pub settings: AudioSettings,
+    join_handle: Option<JoinHandle<()>>,
+    abort: Arc<AtomicBool>,
+    latest: Arc<Latest>,
+    source: Option<AudioSource>,
+    volume: f32,
+    vowels: [f32; 5],
+}
+
+fn decode_pcm16(bytes: &[u8], out: &mut Vec<f32>) {
+    out.clear();
+    out.extend(
+        bytes
+            .chunks_exact(2)
+            .map(|x| i16::from_le_bytes([x[0], x[1]]) as f32 / 32768.0),
+    );
+}
+
+impl AudioSystem {
+    pub fn new() -> Self {
+        AudioSystem {
+            settings: AudioSettings::default(),
+            join_handle: None,
+            abort: Arc::new(AtomicBool::new(false)),
+            latest: Arc::new(Mutex::new(None)),
+            source: None,
+            volume: 0.0,
+            vowels: [0.0; 5],
+        }
+    }
+
+    pub fn source(&self) -> Option<&AudioSource> {
+        self.source.as_ref()
+    }
+
+    /// Whether audio has been analysed recently.
+    pub fn receiving(&self) -> bool {
+        self.latest
+            .lock()
+            .unwrap()
+            .is_some_and(|(_, at)| at.elapsed() < AUDIO_TIMEOUT)
+    }
+
+    pub fn active(&self) -> bool {
+        self.join_handle
+            .as_ref()
+            .map(|x| !x.is_finished())
+            .unwrap_or(false)
+    }
+
+    /// Moves the exposed values towards the latest analysed audio.
+    pub fn update(&mut self, dt: f32) {
+        let latest = *self.latest.lock().unwrap();
+        let (volume, vowels) = match latest {
+            Some((levels, at)) if at.elapsed() < AUDIO_TIMEOUT => {
+                let loudness = levels.loudness + self.settings.gain;
+                let floor = self.settings.floor.min(-1.0);
+                let volume = ((loudness - floor) / -floor).clamp(0.0, 1.0);
+                (volume, levels.vowels)
+            }
+            _ => (0.0, [0.0; 5]),
+        };
+
+        let keep = self.settings.smoothing.clamp(0.0, 0.99).powf(dt * 60.0);
+        self.volume = volume + (self.volume - volume) * keep;
+        for (current, target) in self.vowels.iter_mut().zip(vowels) {
+            *current = target + (*current - target) * keep;
+        }
+    }
+
+    /// The current value of an audio input, from 0 to 1, or `None` if no
+    /// audio source is connected.
+    ///
+    /// Vowels are scaled by the volume, so the mouth closes in silence.
+    pub fn value(&self, kind: AudioKind) -> Option<f32> {
+        self.join_handle.as_ref()?;
+
+        Some(match kind {
+            AudioKind::Volume => self.volume,
+            AudioKind::A => self.vowels[0] * self.volume,
+            AudioKind::I => self.vowels[1] * self.volume,
+            AudioKind::U => self.vowels[2] * self.volume,
+            AudioKind::E => self.vowels[3] * self.volume,
+            AudioKind::O => self.vowels[4] * self.volume,
+        })
+    }
+
+    /// Stops reading audio. A pipe blocked waiting for data is left to end on
+    /// its own, as nothing else can wake it.
+    pub fn disconnect(&mut self) {
+        self.abort.store(true, Ordering::Relaxed);
+        self.join_handle = None;
+        self.source = None;
+        self.volume = 0.0;
+        self.vowels = [0.0; 5];
+    }
+
+    pub fn connect(&mut self, source: AudioSource) -> Result<(), AudioError> {
+        if self.join_handle.is_some() {
+            return Err(AudioError::AlreadyConnected);
+        }
+
+        // Each connection gets its own state, so a reader that outlives its
+        // disconnection can't affect a newer one.
+        let abort = Arc::new(AtomicBool::new(false));
+        let latest = Arc::new(Mutex::new(None));
+
+        let handle = match &source {
+            AudioSource::File(path) => {
+                let wav = Wav::open(path)?;
+                let (abort, latest) = (Arc::clone(&abort), Arc::clone(&latest));
+                spawn(move || Self::play_file(wav, &abort, &latest))
+            }
+            AudioSource::Udp(addr, rate) => {
+                let sock = UdpSocket::bind(addr)?;
+                sock.set_read_timeout(Some(Duration::from_millis(500)))?;
+                let (abort, latest, rate) = (Arc::clone(&abort), Arc::clone(&latest), *rate);
+                spawn(move || Self::read_udp(sock, rate, &abort, &latest))
+            }
+            AudioSource::Pipe(path, rate) => {
+                let path = path.clone();
+                let (abort, latest, rate) = (Arc::clone(&abort), Arc::clone(&latest), *rate);
+                spawn(move || {
+                    // Opening a pipe waits for a writer, so happens here.
+                    match File::open(&path) {
+                        Ok(file) => Self::read_pipe(file, rate, &abort, &latest),
+                        Err(e) => eprintln!("Error opening audio pipe: {}", e),
+                    }
+                })
+            }
+        };
+
+        self.abort = abort;
+        self.latest = latest;
+        self.join_handle = Some(handle);
+        self.source = Some(source);
+        Ok(())
+    }
+
+    fn play_file(wav: Wav, abort: &AtomicBool, latest: &Latest) {
+        let mut analyzer = Analyzer::new(wav.sample_rate);
+        // Fed in steps of 10ms, paced against the clock so the mouth keeps
+        // time with the file.
+        let step = (wav.sample_rate as usize / 100).max(1);
+        let start = Instant::now();
+
+        for (ind, chunk) in wav.samples.chunks(step).enumerate() {
+            if abort.load(Ordering::Relaxed) {
+                return;
+            }
+
+            if let Some(levels) = analyzer.push(chunk) {
+                *latest.lock().unwrap() = Some((levels, Instant::now()));
+            }
+
+            let due =
+                Duration::from_secs_f64((ind + 1) as f64 * step as f64 / wav.sample_rate as f64);
+            if let Some(wait) = due.checked_sub(start.elapsed()) {
+                sleep(wait);
+            }
+        }
+    }
+
+    fn read_udp(sock: UdpSocket, sample_rate: u32, abort: &AtomicBool, latest: &Latest) {
+        let mut analyzer = Analyzer::new(sample_rate);
+        let mut samples = Vec::new();
+
+        while !abort.load(Ordering::Relaxed) {
+            let mut buf = [0u8; 65536];
+            match sock.recv_from(&mut buf) {
+                Ok((size, _)) => {
+                    decode_pcm16(&buf[..size], &mut samples);
+                    if let Some(levels) = analyzer.push(&samples) {
+                        *latest.lock().unwrap() = Some((levels, Instant::now()));
+                    }
+                }
+                Err(e) => {
+                    if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::WouldBlock {
+                        continue;
+                    }
+                    println!("Error receiving audio: {}", e);
+                }
+            }
+        }
+    }
+
+    fn read_pipe(mut file: File, sample_rate: u32, abort: &AtomicBool, latest: &Latest) {
+        let mut analyzer = Analyzer::new(sample_rate);
+        let mut samples = Vec::new();
+        let mut buf = [0u8; 4096];
+        // A read can end halfway through a sample.
+        let mut carried = 0;
+
+        while !abort.load(Ordering::Relaxed) {
+            match file.read(&mut buf[carried..]) {
+                Ok(0) => break,
+                Ok(size) => {
+                    let filled = carried + size;
+                    let whole = filled & !1;
+                    decode_pcm16(&buf[..whole], &mut samples);
+                    if let Some(levels) = analyzer.push(&samples) {
+                        *latest.lock().unwrap() = Some((levels, Instant::now()));
+                    }
+
+                    buf.copy_within(whole..filled, 0);
+                    carried = filled - whole;
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    eprintln!("Error reading audio pipe: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
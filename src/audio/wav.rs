@@ -0,0 +1,102 @@
+use std::{fs, io, path::Path};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WavError {
+    #[error("could not read the audio file")]
+    Io(#[from] io::Error),
+    #[error("not a WAV file")]
+    NotWav,
+    #[error("the WAV file is missing its {0} chunk")]
+    MissingChunk(&'static str),
+    #[error("unsupported WAV format ({bits} bit, format tag {tag})")]
+    Unsupported { tag: u16, bits: u16 },
+}
+
+/// A decoded WAV file, mixed down to mono.
+#[derive(Clone, Debug)]
+pub struct Wav {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+impl Wav {
+    pub fn open(path: &Path) -> Result<Wav, WavError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Decodes 8, 16, 24 or 32 bit integer and 32 bit float PCM.
+    pub fn parse(bytes: &[u8]) -> Result<Wav, WavError> {
+        if bytes.get(0..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+            return Err(WavError::NotWav);
+        }
+
+        let mut format = None;
+        let mut data = None;
+        let mut at = 12;
+        while let (Some(id), Some(len)) = (bytes.get(at..at + 4), u32_at(bytes, at + 4)) {
+            let start = at + 8;
+            let end = (start + len as usize).min(bytes.len());
+            match id {
+                b"fmt " => format = Some(&bytes[start..end]),
+                b"data" => data = Some(&bytes[start..end]),
+                _ => {}
+            }
+            // Chunks are padded to an even length.
+            at = end + (len as usize & 1);
+        }
+
+        let format = format.ok_or(WavError::MissingChunk("format"))?;
+        let data = data.ok_or(WavError::MissingChunk("data"))?;
+
+        let (Some(mut tag), Some(channels), Some(sample_rate), Some(bits)) = (
+            u16_at(format, 0),
+            u16_at(format, 2),
+            u32_at(format, 4),
+            u16_at(format, 14),
+        ) else {
+            return Err(WavError::MissingChunk("format"));
+        };
+
+        // Extensible files keep the real format tag in their sub-format.
+        if tag == 0xfffe {
+            tag = u16_at(format, 24).unwrap_or(tag);
+        }
+
+        let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+            (1, 8) => |x| (x[0] as f32 - 128.0) / 128.0,
+            (1, 16) => |x| i16::from_le_bytes([x[0], x[1]]) as f32 / 32768.0,
+            (1, 24) => |x| i32::from_le_bytes([0, x[0], x[1], x[2]]) as f32 / 2147483648.0,
+            (1, 32) => |x| i32::from_le_bytes([x[0], x[1], x[2], x[3]]) as f32 / 2147483648.0,
+            (3, 32) => |x| f32::from_le_bytes([x[0], x[1], x[2], x[3]]),
+            _ => return Err(WavError::Unsupported { tag, bits }),
+        };
+
+        let channels = channels.max(1) as usize;
+        let frame_size = bits as usize / 8 * channels;
+        let samples = data
+            .chunks_exact(frame_size)
+            .map(|frame| {
+                frame
+                    .chunks_exact(bits as usize / 8)
+                    .map(decode)
+                    .sum::<f32>()
+                    / channels as f32
+            })
+            .collect();
+
+        Ok(Wav {
+            sample_rate,
+            samples,
+        })
+    }
+}
@@ -1,18 +1,31 @@
-use std::sync::{
-    atomic::{AtomicU32, Ordering},
-    Arc, Mutex,
+use std::{
+    ffi::OsString,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
 };
 
-use arc_swap::ArcSwapOption;
+use arc_swap::{ArcSwap, ArcSwapOption};
 use inox2d::model::Model;
 
-use crate::tracker::ParamBindings;
+use crate::{
+    app::model::ModelData, mixer::ParamMixer, parts::PartVisibility, static_puppet::StaticPuppet,
+    tracker::ParamBindings,
+};
 
 #[derive(Default, Debug)]
 pub struct DisplayedModel {
     displayed: ArcSwapOption<Model>,
+    // Shown instead of an Inochi2D model, never alongside one.
+    displayed_static: ArcSwapOption<StaticPuppet>,
+    // What's stored for the displayed model, which stays with the model
+    // while another is shown.
+    data: ArcSwap<ModelData>,
     generation: AtomicU32,
     pub bindings: Mutex<ParamBindings>,
+    pub mixer: Mutex<ParamMixer>,
+    pub parts: Mutex<PartVisibility>,
 }
 
 impl DisplayedModel {
@@ -26,8 +39,36 @@ impl DisplayedModel {
         (displayed, generation)
     }
 
-    pub fn swap_model(&self, model: Option<Model>) {
-        self.displayed.store(model.map(|x| Arc::new(x)));
+    /// The displayed static puppet, if one is shown rather than an Inochi2D
+    /// model.
+    pub fn current_static(&self) -> Option<Arc<StaticPuppet>> {
+        self.displayed_static.load_full()
+    }
+
+    /// The file name of the displayed model, which its stored data is keyed by.
+    pub fn current_name(&self) -> Option<Arc<OsString>> {
+        self.data.load().name()
+    }
+
+    /// What's stored for the displayed model, which is empty and never saved
+    /// while none is shown.
+    pub fn data(&self) -> Arc<ModelData> {
+        self.data.load_full()
+    }
+
+    pub fn swap_model(&self, model: Option<(Arc<ModelData>, Model)>) {
+        let (data, model) = model.unzip();
+        self.data.store(data.unwrap_or_default());
+        self.displayed.store(model.map(Arc::new));
+        self.displayed_static.store(None);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    pub fn swap_static(&self, puppet: (Arc<ModelData>, StaticPuppet)) {
+        let (data, puppet) = puppet;
+        self.data.store(data);
+        self.displayed.store(None);
+        self.displayed_static.store(Some(Arc::new(puppet)));
         self.generation.fetch_add(1, Ordering::Release);
     }
 }
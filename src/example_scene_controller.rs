@@ -55,6 +55,15 @@ impl ExampleSceneController {
         self.current_elapsed = self.start.elapsed().as_secs_f32();
     }
 
+    /// Moves the camera straight to a position and scale, skipping the
+    /// smooth scrolling.
+    pub fn reset(&mut self, camera: &mut Camera, position: Vec2, scale: Vec2) {
+        camera.position = position;
+        camera.scale = scale;
+        self.camera_pos = position;
+        self.hard_scale = scale;
+    }
+
     pub fn interact(&mut self, window: &Window, event: &WindowEvent, camera: &Camera) {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
@@ -0,0 +1,233 @@
+//! Expression presets, named sets of parameter values that fade in and out
+//! on top of tracking and animations.
+
+use std::collections::{BTreeMap, HashMap};
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
+
+use crate::{
+    animation::step_towards,
+    app::model::Stored,
+    hotkey::Hotkey,
+    mixer::{LayerId, ParamMixer},
+};
+
+/// What pressing an expression's hotkey does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    /// Each press switches the expression on or off.
+    Toggle,
+    /// The expression is on while the key is held down.
+    Hold,
+}
+
+impl Activation {
+    pub const ALL: [Activation; 2] = [Activation::Toggle, Activation::Hold];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Activation::Toggle => "Toggle",
+            Activation::Hold => "Hold",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Expression {
+    pub name: String,
+    pub values: BTreeMap<String, Vec2>,
+    pub hotkey: Option<Hotkey>,
+    pub activation: Activation,
+    /// Turning on an expression turns off every other one in its group.
+    pub group: String,
+    /// Seconds after which the expression turns itself off again.
+    pub auto_off: Option<f32>,
+    /// Seconds taken to fade the expression in.
+    pub fade_in: f32,
+    /// Seconds taken to fade the expression out.
+    pub fade_out: f32,
+}
+
+impl Expression {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            values: BTreeMap::new(),
+            hotkey: None,
+            activation: Activation::Toggle,
+            group: String::new(),
+            auto_off: None,
+            fade_in: 0.2,
+            fade_out: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    active: bool,
+    // Seconds since the expression was turned on.
+    elapsed: f32,
+    weight: f32,
+}
+
+/// The expressions stored for the displayed model, and how far each one is
+/// faded in.
+#[derive(Debug, Default)]
+pub struct ExpressionPlayer {
+    expressions: Vec<Expression>,
+    states: HashMap<String, State>,
+}
+
+impl Stored for ExpressionPlayer {
+    const FILE: &'static str = "expressions.json";
+    const NAME: &'static str = "expressions";
+    type Data = Vec<Expression>;
+
+    fn from_stored(expressions: Vec<Expression>) -> Self {
+        Self {
+            expressions,
+            ..Self::default()
+        }
+    }
+
+    fn stored(&self) -> &Vec<Expression> {
+        &self.expressions
+    }
+}
+
+impl ExpressionPlayer {
+    pub fn expressions(&self) -> &[Expression] {
+        &self.expressions
+    }
+
+    pub fn expressions_mut(&mut self) -> &mut Vec<Expression> {
+        &mut self.expressions
+    }
+
+    pub fn expression(&self, name: &str) -> Option<&Expression> {
+        self.expressions.iter().find(|x| x.name == name)
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        self.states.get(name).is_some_and(|x| x.active)
+    }
+
+    /// How far an expression is faded in, from 0 to 1.
+    pub fn weight(&self, name: &str) -> f32 {
+        self.states.get(name).map_or(0.0, |x| x.weight)
+    }
+
+    pub fn set_active(&mut self, name: &str, active: bool) {
+        let Some(expression) = self.expression(name) else {
+            return;
+        };
+
+        if active && !expression.group.is_empty() {
+            let group = expression.group.clone();
+            for other in &self.expressions {
+                if other.group == group && other.name != name {
+                    if let Some(state) = self.states.get_mut(&other.name) {
+                        state.active = false;
+                    }
+                }
+            }
+        }
+
+        let state = self.states.entry(name.to_owned()).or_default();
+        if active && !state.active {
+            state.elapsed = 0.0;
+        }
+        state.active = active;
+    }
+
+    pub fn toggle(&mut self, name: &str) {
+        self.set_active(name, !self.is_active(name));
+    }
+
+    /// Turns every expression off.
+    pub fn clear(&mut self) {
+        for state in self.states.values_mut() {
+            state.active = false;
+        }
+    }
+
+    /// Reacts to a hotkey being pressed, returning whether any expression
+    /// used it.
+    pub fn key_pressed(&mut self, hotkey: Hotkey) -> bool {
+        let matching: Vec<_> = self
+            .expressions
+            .iter()
+            .filter(|x| x.hotkey == Some(hotkey))
+            .map(|x| (x.name.clone(), x.activation))
+            .collect();
+
+        for (name, activation) in &matching {
+            match activation {
+                Activation::Toggle => self.toggle(name),
+                Activation::Hold => self.set_active(name, true),
+            }
+        }
+
+        !matching.is_empty()
+    }
+
+    /// Turns off held expressions once their key is let go. Modifiers are
+    /// ignored, as they may have been released first.
+    pub fn key_released(&mut self, key: VirtualKeyCode) {
+        let held: Vec<_> = self
+            .expressions
+            .iter()
+            .filter(|x| x.activation == Activation::Hold && x.hotkey.is_some_and(|x| x.key == key))
+            .map(|x| x.name.clone())
+            .collect();
+
+        for name in held {
+            self.set_active(&name, false);
+        }
+    }
+
+    /// Advances fades and timers and writes the expressions to the mixer.
+    pub fn update(&mut self, dt: f32, mixer: &mut ParamMixer) {
+        mixer.clear(LayerId::Expressions);
+
+        let states = &mut self.states;
+        states.retain(|name, _| self.expressions.iter().any(|x| &x.name == name));
+
+        let mut mixed: BTreeMap<&str, (Vec2, f32)> = BTreeMap::new();
+        for expression in &self.expressions {
+            let Some(state) = states.get_mut(&expression.name) else {
+                continue;
+            };
+
+            if state.active {
+                state.elapsed += dt;
+                if expression.auto_off.is_some_and(|x| state.elapsed >= x) {
+                    state.active = false;
+                }
+            }
+
+            state.weight = if state.active {
+                step_towards(state.weight, 1.0, dt, expression.fade_in)
+            } else {
+                step_towards(state.weight, 0.0, dt, expression.fade_out)
+            };
+            if state.weight <= 0.0 {
+                continue;
+            }
+
+            // Later expressions are blended over earlier ones.
+            for (param, value) in &expression.values {
+                let entry = mixed.entry(param).or_insert((*value, 0.0));
+                entry.0 = entry.0.lerp(*value, state.weight);
+                entry.1 += (1.0 - entry.1) * state.weight;
+            }
+        }
+
+        for (param, (value, weight)) in mixed {
+            mixer.set_weighted(LayerId::Expressions, param, value, weight);
+        }
+    }
+}
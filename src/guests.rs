@@ -0,0 +1,195 @@
+//! The puppets on stage alongside the shown model. Each is driven only by
+//! its own bindings, from the shared tracker or one of its own.
+
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use egui_wgpu::wgpu;
+use glam::UVec2;
+
+use crate::{
+    app::{
+        model::Models,
+        stage::{Stage, TrackerSource, Transform},
+    },
+    apply_bindings,
+    mixer::ParamMixer,
+    stage_puppet::StagePuppet,
+    tracker::{Inputs, Smoothing, TrackerSystem},
+};
+
+pub struct GuestPuppet {
+    pub path: PathBuf,
+    pub puppet: StagePuppet,
+    pub transform: Transform,
+    // Which version of the model's file the puppet was set up from.
+    modified: Option<SystemTime>,
+    mixer: ParamMixer,
+    smoothing: Smoothing,
+    // A tracker of the guest's own, and the port it listens on.
+    tracker: Option<(u16, TrackerSystem)>,
+    // Why the guest's own tracker couldn't start listening.
+    tracker_error: Option<String>,
+}
+
+impl GuestPuppet {
+    fn set_tracker(&mut self, source: TrackerSource) {
+        let port = match source {
+            TrackerSource::Shared => None,
+            TrackerSource::Port(port) => Some(port),
+        };
+        if self.tracker.as_ref().map(|x| x.0) == port {
+            // A port that was taken, like one the previous tracker hadn't
+            // let go of yet, is tried again until it's free.
+            if self.tracker_error.is_some() {
+                if let Some((port, tracker)) = &mut self.tracker {
+                    self.tracker_error = listen(tracker, *port);
+                }
+            }
+            return;
+        }
+
+        // Joining the network thread could hold up the frame, so it's left
+        // to stop by itself.
+        if let Some((_, mut tracker)) = self.tracker.take() {
+            tracker.stop();
+        }
+        self.tracker_error = None;
+        if let Some(port) = port {
+            let mut tracker = TrackerSystem::new();
+            self.tracker_error = listen(&mut tracker, port);
+            self.tracker = Some((port, tracker));
+        }
+        self.smoothing.clear();
+    }
+}
+
+/// Starts a guest's tracker listening, returning why it couldn't.
+fn listen(tracker: &mut TrackerSystem, port: u16) -> Option<String> {
+    tracker
+        .connect(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)))
+        .err()
+        .map(|e| e.to_string())
+}
+
+#[derive(Default)]
+pub struct Guests {
+    puppets: Vec<GuestPuppet>,
+}
+
+impl Guests {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut GuestPuppet> {
+        self.puppets.iter_mut()
+    }
+
+    /// Brings the guests in line with the stage, setting up those that have
+    /// finished loading and dropping those no longer on it.
+    pub fn sync(
+        &mut self,
+        stage: &Stage,
+        models: &Models,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: UVec2,
+    ) {
+        self.puppets.retain_mut(|puppet| {
+            let kept = stage.guest(&puppet.path).is_some() && models.is_loaded(&puppet.path);
+            if !kept {
+                if let Some((_, tracker)) = &mut puppet.tracker {
+                    tracker.stop();
+                }
+            }
+            kept
+        });
+
+        for guest in &stage.guests {
+            let Some(loaded) = models.puppet(&guest.path) else {
+                continue;
+            };
+            let ind = match self.puppets.iter().position(|x| x.path == guest.path) {
+                Some(ind) => ind,
+                None => {
+                    self.puppets.push(GuestPuppet {
+                        path: guest.path.clone(),
+                        puppet: StagePuppet::new(device, queue, loaded.kind(), size),
+                        transform: guest.transform,
+                        modified: loaded.modified(),
+                        mixer: ParamMixer::default(),
+                        smoothing: Smoothing::default(),
+                        tracker: None,
+                        tracker_error: None,
+                    });
+                    self.puppets.len() - 1
+                }
+            };
+
+            let puppet = &mut self.puppets[ind];
+            // Reloaded models are set up again.
+            if puppet.modified != loaded.modified() {
+                puppet.puppet = StagePuppet::new(device, queue, loaded.kind(), size);
+                puppet.modified = loaded.modified();
+                puppet.mixer = ParamMixer::default();
+            }
+            puppet.transform = guest.transform;
+            puppet.set_tracker(guest.tracker);
+        }
+    }
+
+    /// Why a guest's own tracker isn't listening, if it isn't.
+    pub fn tracker_error(&self, path: &Path) -> Option<String> {
+        let puppet = self.puppets.iter().find(|x| x.path == path)?;
+        puppet.tracker_error.clone()
+    }
+
+    /// Moves each guest to follow its tracker, through its bindings for
+    /// Inochi2D puppets.
+    pub fn update(&mut self, dt: f32, models: &Models, shared: &TrackerSystem) {
+        for puppet in &mut self.puppets {
+            let Some(loaded) = models.puppet(&puppet.path) else {
+                continue;
+            };
+
+            let tracker = match &mut puppet.tracker {
+                Some((_, tracker)) => {
+                    tracker.update(dt);
+                    &*tracker
+                }
+                None => shared,
+            };
+            match &mut puppet.puppet {
+                StagePuppet::Inochi2D(model, _) => {
+                    apply_bindings(
+                        &mut puppet.mixer,
+                        loaded.bindings(),
+                        tracker,
+                        &mut puppet.smoothing,
+                        dt,
+                    );
+                    puppet.mixer.apply(&mut model.puppet, dt);
+                }
+                stage_puppet => {
+                    let data = tracker.data().lock().unwrap();
+                    let inputs = Inputs {
+                        tracker: &data,
+                        calibration: tracker.calibration(),
+                        generators: tracker.generators(),
+                        audio: tracker.audio(),
+                        parameters: &HashMap::new(),
+                    };
+                    let overrides = loaded.data().static_settings.lock().unwrap();
+                    stage_puppet.update_static(dt, &inputs, &overrides);
+                }
+            }
+        }
+    }
+
+    pub fn resize(&mut self, size: UVec2) {
+        for puppet in &mut self.puppets {
+            puppet.puppet.resize(size);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use egui::{Button, ComboBox, Context, DragValue, ProgressBar, RichText, TextEdit};
+
+use crate::{
+    animation::{Clip, Playback},
+    app::AppState,
+    gui::save_when_settled,
+};
+
+pub struct Animations {
+    pub open: bool,
+    state: Arc<AppState>,
+    new_name: String,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl Animations {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            new_name: String::new(),
+            dirty: false,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        egui::Window::new("Animations")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                let data = self.state.displayed.data();
+                let mut player = data.animations.lock().unwrap();
+
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(&mut self.new_name).hint_text("Clip name"));
+
+                    let name = self.new_name.trim();
+                    let valid = !name.is_empty() && player.clip(name).is_none();
+                    if ui.add_enabled(valid, Button::new("New Clip")).clicked() {
+                        player.clips_mut().push(Clip::new(name.to_owned()));
+                        self.new_name.clear();
+                        self.dirty = true;
+                    }
+
+                    if ui.button("Stop All").clicked() {
+                        player.stop_all();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                if player.clips().is_empty() {
+                    ui.label(RichText::new("No clips for this model").italics());
+                }
+
+                let mut to_delete = None;
+                let names: Vec<_> = player.clips().iter().map(|x| x.name.clone()).collect();
+                for (ind, name) in names.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(name).strong());
+
+                        if player.is_playing(name) {
+                            if ui.button("Stop").clicked() {
+                                player.stop(name);
+                            }
+                        } else if ui.button("Play").clicked() {
+                            player.play(name);
+                        }
+
+                        if let Some(progress) = player.progress(name) {
+                            ui.add(ProgressBar::new(progress).desired_width(80.0));
+                        }
+
+                        if ui.button("Delete").clicked() {
+                            to_delete = Some(ind);
+                        }
+                    });
+
+                    let clip = &mut player.clips_mut()[ind];
+                    ui.horizontal(|ui| {
+                        let before = clip.clone();
+
+                        ComboBox::from_id_source(("clip_playback", name))
+                            .selected_text(clip.playback.name())
+                            .show_ui(ui, |ui| {
+                                for playback in Playback::ALL {
+                                    ui.selectable_value(
+                                        &mut clip.playback,
+                                        playback,
+                                        playback.name(),
+                                    );
+                                }
+                            });
+
+                        ui.label("Length:");
+                        ui.add(
+                            DragValue::new(&mut clip.length)
+                                .speed(0.05)
+                                .clamp_range(0.1..=600.0)
+                                .suffix("s"),
+                        );
+                        ui.label("Blend In:");
+                        ui.add(
+                            DragValue::new(&mut clip.blend_in)
+                                .speed(0.01)
+                                .clamp_range(0.0..=10.0)
+                                .suffix("s"),
+                        );
+                        ui.label("Blend Out:");
+                        ui.add(
+                            DragValue::new(&mut clip.blend_out)
+                                .speed(0.01)
+                                .clamp_range(0.0..=10.0)
+                                .suffix("s"),
+                        );
+
+                        self.dirty |= *clip != before;
+                    });
+
+                    ui.label(format!("{} animated parameters", clip.tracks.len()));
+                    ui.separator();
+                }
+
+                if let Some(ind) = to_delete {
+                    let clip = player.clips_mut().remove(ind);
+                    player.stop(&clip.name);
+                    self.dirty = true;
+                }
+            });
+
+        let data = self.state.displayed.data();
+        let player = data.animations.lock().unwrap();
+        save_when_settled(ctx, &mut self.dirty, &data, &*player, &mut self.error);
+    }
+}
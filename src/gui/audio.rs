@@ -0,0 +1,188 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use egui::{Button, ComboBox, Context, DragValue, ProgressBar, Slider, TextEdit};
+
+use crate::{
+    audio::{AudioKind, AudioSource},
+    tracker::TrackerSystem,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SourceKind {
+    File,
+    Udp,
+    Pipe,
+}
+
+impl SourceKind {
+    const ALL: [SourceKind; 3] = [SourceKind::File, SourceKind::Udp, SourceKind::Pipe];
+
+    fn name(&self) -> &str {
+        match self {
+            SourceKind::File => "WAV File",
+            SourceKind::Udp => "UDP Stream",
+            SourceKind::Pipe => "Pipe",
+        }
+    }
+}
+
+pub struct Audio {
+    pub open: bool,
+    kind: SourceKind,
+    path_string: String,
+    port_string: String,
+    sample_rate: u32,
+    error: Option<String>,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self {
+            open: false,
+            kind: SourceKind::File,
+            path_string: String::new(),
+            port_string: String::new(),
+            sample_rate: 48000,
+            error: None,
+        }
+    }
+}
+
+impl Audio {
+    pub fn draw(&mut self, ctx: &Context, tracker_system: &mut TrackerSystem) {
+        egui::Window::new("Lip Sync")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Voice volume and vowel shapes, which can be bound like any tracker input. \
+                    Streams are raw 16 bit little-endian mono PCM.",
+                );
+
+                let audio = tracker_system.audio_mut();
+                ui.label(match audio.source() {
+                    Some(source) if audio.active() => format!("Listening to {}", source.name()),
+                    Some(source) => format!("Finished {}", source.name()),
+                    None => "Not connected".to_string(),
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Source:");
+                    ComboBox::from_id_source("audio_source")
+                        .selected_text(self.kind.name())
+                        .show_ui(ui, |ui| {
+                            for i in SourceKind::ALL {
+                                ui.selectable_value(&mut self.kind, i, i.name());
+                            }
+                        });
+                });
+
+                let source = match self.kind {
+                    SourceKind::File | SourceKind::Pipe => {
+                        ui.horizontal(|ui| {
+                            ui.label("Path:");
+                            ui.add(TextEdit::singleline(&mut self.path_string));
+                            if self.kind == SourceKind::File && ui.button("Browse").clicked() {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("WAV Files", &["wav"])
+                                    .pick_file()
+                                {
+                                    self.path_string = path.display().to_string();
+                                }
+                            }
+                        });
+
+                        let path = PathBuf::from(&self.path_string);
+                        (!self.path_string.is_empty()).then(|| match self.kind {
+                            SourceKind::File => AudioSource::File(path),
+                            _ => AudioSource::Pipe(path, self.sample_rate),
+                        })
+                    }
+                    SourceKind::Udp => {
+                        ui.horizontal(|ui| {
+                            ui.label("Port:");
+                            ui.add(TextEdit::singleline(&mut self.port_string).hint_text("39540"));
+                        });
+
+                        u16::from_str(&self.port_string).ok().map(|port| {
+                            let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+                            AudioSource::Udp(addr, self.sample_rate)
+                        })
+                    }
+                };
+
+                if self.kind != SourceKind::File {
+                    ui.horizontal(|ui| {
+                        ui.label("Sample Rate (Hz):");
+                        ui.add(
+                            DragValue::new(&mut self.sample_rate)
+                                .speed(100)
+                                .clamp_range(8000..=192000),
+                        );
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(source.is_some(), Button::new("Connect"))
+                        .clicked()
+                    {
+                        audio.disconnect();
+                        self.error = audio.connect(source.unwrap()).err().map(|e| e.to_string());
+                    }
+                    if ui
+                        .add_enabled(audio.source().is_some(), Button::new("Disconnect"))
+                        .clicked()
+                    {
+                        audio.disconnect();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                let settings = &mut audio.settings;
+                ui.horizontal(|ui| {
+                    ui.label("Gain (dB):");
+                    ui.add(Slider::new(&mut settings.gain, -20.0..=40.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Silence Below (dB):");
+                    ui.add(Slider::new(&mut settings.floor, -90.0..=-10.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Smoothing:");
+                    ui.add(
+                        DragValue::new(&mut settings.smoothing)
+                            .speed(0.01)
+                            .clamp_range(0.0..=0.99),
+                    );
+                });
+
+                ui.separator();
+
+                for kind in AudioKind::ALL {
+                    match audio.value(kind) {
+                        Some(val) => {
+                            ui.add(ProgressBar::new(val).text(format!(
+                                "{}: {:.2}",
+                                kind.name(),
+                                val
+                            )));
+                        }
+                        None => {
+                            ui.label(format!("{}: inactive", kind.name()));
+                        }
+                    }
+                }
+            });
+    }
+}
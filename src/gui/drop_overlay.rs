@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Align2, Color32, Context, FontId, Id, LayerId, Order};
+
+use crate::app::model::is_model_file;
+
+/// Covers the window while files are dragged over it, saying what dropping
+/// them will do.
+#[derive(Default)]
+pub struct DropOverlay {
+    hovered: Vec<PathBuf>,
+    // How many of the files are models, counted as the first one lands since
+    // they're dropped one at a time.
+    dropping: Option<usize>,
+}
+
+impl DropOverlay {
+    pub fn file_hovered(&mut self, path: PathBuf) {
+        self.hovered.push(path);
+    }
+
+    /// Takes a dropped file off those hovered, returning whether it should be
+    /// shown. A lone model is shown, while several are only loaded.
+    pub fn file_dropped(&mut self, path: &Path) -> bool {
+        let models = *self
+            .dropping
+            .get_or_insert_with(|| self.hovered.iter().filter(|x| is_model_file(x)).count());
+        self.hovered.retain(|x| x != path);
+        if self.hovered.is_empty() {
+            self.dropping = None;
+        }
+        models <= 1
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn draw(&self, ctx: &Context) {
+        if self.hovered.is_empty() {
+            return;
+        }
+
+        let models: Vec<_> = self.hovered.iter().filter(|x| is_model_file(x)).collect();
+        let mut text = match models.as_slice() {
+            [] => "Not a model file".to_owned(),
+            [path] => format!(
+                "Drop to show {}",
+                path.file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+            ),
+            models => format!("Drop to load {} models", models.len()),
+        };
+        let others = self.hovered.len() - models.len();
+        if !models.is_empty() && others > 0 {
+            text += &match others {
+                1 => " (1 file isn't a model)".to_owned(),
+                others => format!(" ({} files aren't models)", others),
+            };
+        }
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop_overlay")));
+        let screen = ctx.screen_rect();
+        painter.rect_filled(screen, 0.0, Color32::from_black_alpha(192));
+        painter.text(
+            screen.center(),
+            Align2::CENTER_CENTER,
+            text,
+            FontId::proportional(24.0),
+            Color32::WHITE,
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use egui::{Button, ComboBox, Context, DragValue, ProgressBar, RichText, Slider, TextEdit};
+use winit::event::VirtualKeyCode;
+
+use crate::{
+    app::AppState,
+    expression::{Activation, Expression},
+    gui::save_when_settled,
+    hotkey::Hotkey,
+};
+
+pub struct Expressions {
+    pub open: bool,
+    state: Arc<AppState>,
+    new_name: String,
+    // The expression waiting for a key press to bind to it.
+    capturing: Option<String>,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl Expressions {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            new_name: String::new(),
+            capturing: None,
+            dirty: false,
+            error: None,
+        }
+    }
+
+    /// Binds a pressed hotkey to the expression waiting for one, returning
+    /// whether the press was used up. Escape cancels instead.
+    pub fn capture_hotkey(&mut self, hotkey: Hotkey) -> bool {
+        let Some(name) = self.capturing.take() else {
+            return false;
+        };
+
+        if hotkey.key != VirtualKeyCode::Escape {
+            let data = self.state.displayed.data();
+            let mut player = data.expressions.lock().unwrap();
+            if let Some(expression) = player.expressions_mut().iter_mut().find(|x| x.name == name) {
+                expression.hotkey = Some(hotkey);
+                self.dirty = true;
+            }
+        }
+
+        true
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        let (model, _) = self.state.displayed.current_model();
+
+        egui::Window::new("Expressions")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                let Some(model) = &model else {
+                    ui.label("No Model Shown");
+                    return;
+                };
+
+                let data = self.state.displayed.data();
+                let mut player = data.expressions.lock().unwrap();
+
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(&mut self.new_name).hint_text("Expression name"));
+
+                    let name = self.new_name.trim();
+                    let valid = !name.is_empty() && player.expression(name).is_none();
+                    if ui
+                        .add_enabled(valid, Button::new("New Expression"))
+                        .clicked()
+                    {
+                        player
+                            .expressions_mut()
+                            .push(Expression::new(name.to_owned()));
+                        self.new_name.clear();
+                        self.dirty = true;
+                    }
+
+                    if ui.button("All Off").clicked() {
+                        player.clear();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                if player.expressions().is_empty() {
+                    ui.label(RichText::new("No expressions for this model").italics());
+                }
+
+                let mut params: Vec<_> = model.puppet.parameters.iter().collect();
+                params.sort_by(|a, b| a.0.cmp(b.0));
+
+                let mut to_delete = None;
+                let names: Vec<_> = player
+                    .expressions()
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect();
+                for (ind, name) in names.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(name).strong());
+
+                        let mut active = player.is_active(name);
+                        if ui.toggle_value(&mut active, "Active").changed() {
+                            player.set_active(name, active);
+                        }
+                        ui.add(ProgressBar::new(player.weight(name)).desired_width(80.0));
+
+                        if ui.button("Delete").clicked() {
+                            to_delete = Some(ind);
+                        }
+                    });
+
+                    let expression = &mut player.expressions_mut()[ind];
+                    let before = expression.clone();
+
+                    egui::CollapsingHeader::new("Settings")
+                        .id_source(("expression_settings", name))
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Hotkey:");
+                                let text = if self.capturing.as_ref() == Some(name) {
+                                    "Press a key...".to_owned()
+                                } else {
+                                    expression
+                                        .hotkey
+                                        .map_or("None".to_owned(), |x| x.to_string())
+                                };
+                                if ui.button(text).clicked() {
+                                    self.capturing = Some(name.clone());
+                                }
+                                if expression.hotkey.is_some() && ui.button("Clear").clicked() {
+                                    expression.hotkey = None;
+                                }
+
+                                ComboBox::from_id_source(("expression_activation", name))
+                                    .selected_text(expression.activation.name())
+                                    .show_ui(ui, |ui| {
+                                        for activation in Activation::ALL {
+                                            ui.selectable_value(
+                                                &mut expression.activation,
+                                                activation,
+                                                activation.name(),
+                                            );
+                                        }
+                                    });
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Exclusive Group:");
+                                ui.add(
+                                    TextEdit::singleline(&mut expression.group)
+                                        .hint_text("None")
+                                        .desired_width(100.0),
+                                );
+                            });
+
+                            ui.horizontal(|ui| {
+                                let mut auto_off = expression.auto_off.is_some();
+                                ui.checkbox(&mut auto_off, "Auto Off After");
+                                let mut seconds = expression.auto_off.unwrap_or(3.0);
+                                ui.add_enabled(
+                                    auto_off,
+                                    DragValue::new(&mut seconds)
+                                        .speed(0.05)
+                                        .clamp_range(0.1..=600.0)
+                                        .suffix("s"),
+                                );
+                                expression.auto_off = auto_off.then_some(seconds);
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Fade In:");
+                                ui.add(
+                                    DragValue::new(&mut expression.fade_in)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=10.0)
+                                        .suffix("s"),
+                                );
+                                ui.label("Fade Out:");
+                                ui.add(
+                                    DragValue::new(&mut expression.fade_out)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=10.0)
+                                        .suffix("s"),
+                                );
+                            });
+
+                            ui.separator();
+
+                            let mut to_remove = None;
+                            for (param, value) in &mut expression.values {
+                                let Some(info) = model.puppet.parameters.get(param) else {
+                                    ui.label(format!("{} (missing from model)", param));
+                                    continue;
+                                };
+
+                                ui.horizontal(|ui| {
+                                    ui.add_sized(
+                                        [140.0, 18.0],
+                                        egui::Label::new(param).truncate(true),
+                                    );
+                                    ui.add(Slider::new(&mut value.x, info.min.x..=info.max.x));
+                                    if info.is_vec2 {
+                                        ui.add(Slider::new(&mut value.y, info.min.y..=info.max.y));
+                                    }
+                                    if ui.button("Remove").clicked() {
+                                        to_remove = Some(param.clone());
+                                    }
+                                });
+                            }
+                            if let Some(param) = to_remove {
+                                expression.values.remove(&param);
+                            }
+
+                            ComboBox::from_id_source(("expression_add", name))
+                                .selected_text("Add Parameter")
+                                .show_ui(ui, |ui| {
+                                    for (param, info) in &params {
+                                        if expression.values.contains_key(*param) {
+                                            continue;
+                                        }
+                                        if ui.selectable_label(false, *param).clicked() {
+                                            expression
+                                                .values
+                                                .insert((*param).clone(), info.defaults);
+                                        }
+                                    }
+                                });
+                        });
+
+                    self.dirty |= *expression != before;
+                    ui.separator();
+                }
+
+                if let Some(ind) = to_delete {
+                    let expression = player.expressions_mut().remove(ind);
+                    if self.capturing.as_ref() == Some(&expression.name) {
+                        self.capturing = None;
+                    }
+                    self.dirty = true;
+                }
+            });
+
+        let data = self.state.displayed.data();
+        let player = data.expressions.lock().unwrap();
+        save_when_settled(ctx, &mut self.dirty, &data, &*player, &mut self.error);
+    }
+}
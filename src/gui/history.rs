@@ -0,0 +1,111 @@
+//! A bounded undo/redo history for editor state.
+
+use std::{
+    collections::VecDeque,
+    mem::replace,
+    time::{Duration, Instant},
+};
+
+/// Edits with the same label made within this window are merged into a
+/// single entry, so dragging a value doesn't flood the history.
+const COALESCE_WINDOW: Duration = Duration::from_millis(750);
+
+struct Entry<T> {
+    label: String,
+    state: T,
+    // `None` for entries that were moved by undo/redo, which should never
+    // absorb later edits.
+    last_edit: Option<Instant>,
+}
+
+pub struct History<T> {
+    undo: VecDeque<Entry<T>>,
+    redo: Vec<Entry<T>>,
+    limit: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Records an edit, where `before` is the state prior to the edit.
+    pub fn record(&mut self, label: impl Into<String>, before: T) {
+        let label = label.into();
+        let now = Instant::now();
+        self.redo.clear();
+
+        if let Some(last) = self.undo.back_mut() {
+            if let Some(last_edit) = last.last_edit {
+                if last.label == label && now - last_edit < COALESCE_WINDOW {
+                    last.last_edit = Some(now);
+                    return;
+                }
+            }
+        }
+
+        self.undo.push_back(Entry {
+            label,
+            state: before,
+            last_edit: Some(now),
+        });
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self, current: &mut T) -> bool {
+        let Some(entry) = self.undo.pop_back() else {
+            return false;
+        };
+
+        let state = replace(current, entry.state);
+        self.redo.push(Entry {
+            label: entry.label,
+            state,
+            last_edit: None,
+        });
+        true
+    }
+
+    pub fn redo(&mut self, current: &mut T) -> bool {
+        let Some(entry) = self.redo.pop() else {
+            return false;
+        };
+
+        let state = replace(current, entry.state);
+        self.undo.push_back(Entry {
+            label: entry.label,
+            state,
+            last_edit: None,
+        });
+        true
+    }
+
+    /// Labels of undoable edits, oldest first.
+    pub fn undo_labels(&self) -> impl Iterator<Item = &str> {
+        self.undo.iter().map(|x| x.label.as_str())
+    }
+
+    /// Labels of redoable edits, next to be redone first.
+    pub fn redo_labels(&self) -> impl Iterator<Item = &str> {
+        self.redo.iter().rev().map(|x| x.label.as_str())
+    }
+}
@@ -0,0 +1,113 @@
+use egui::{ComboBox, Context, DragValue, ProgressBar, Slider, Ui};
+
+use crate::tracker::{GeneratorKind, GeneratorMode, Generators, TrackerSystem};
+
+#[derive(Default)]
+pub struct IdleMotion {
+    pub open: bool,
+}
+
+impl IdleMotion {
+    pub fn draw(&mut self, ctx: &Context, tracker_system: &mut TrackerSystem) {
+        egui::Window::new("Idle Motion")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Generated inputs which can be bound like any tracker input. \
+                    Generators set to \"When Untracked\" only run while no tracking data arrives, \
+                    or stand in for a binding's own input once it's lost when chosen as its fallback.",
+                );
+                ui.label(if tracker_system.receiving() {
+                    "Tracking data: receiving"
+                } else {
+                    "Tracking data: none"
+                });
+
+                let generators = tracker_system.generators_mut();
+
+                ui.separator();
+                ui.heading("Auto Blink");
+                Self::mode_select(ui, "blink", &mut generators.settings.blink.mode);
+                let blink = &mut generators.settings.blink;
+                ui.horizontal(|ui| {
+                    ui.label("Average Interval (s):");
+                    ui.add(
+                        DragValue::new(&mut blink.interval)
+                            .speed(0.1)
+                            .clamp_range(0.5..=30.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Blink Duration (s):");
+                    ui.add(
+                        DragValue::new(&mut blink.duration)
+                            .speed(0.01)
+                            .clamp_range(0.05..=1.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Double Blink Chance:");
+                    ui.add(Slider::new(&mut blink.double_chance, 0.0..=1.0));
+                });
+                Self::value_bar(ui, generators, GeneratorKind::Blink);
+
+                ui.separator();
+                ui.heading("Breathing");
+                Self::mode_select(ui, "breath", &mut generators.settings.breath.mode);
+                ui.horizontal(|ui| {
+                    ui.label("Seconds per Breath:");
+                    ui.add(
+                        DragValue::new(&mut generators.settings.breath.period)
+                            .speed(0.1)
+                            .clamp_range(0.5..=20.0),
+                    );
+                });
+                Self::value_bar(ui, generators, GeneratorKind::Breath);
+
+                ui.separator();
+                ui.heading("Idle Sway");
+                Self::mode_select(ui, "sway", &mut generators.settings.sway.mode);
+                ui.horizontal(|ui| {
+                    ui.label("Speed:");
+                    ui.add(Slider::new(&mut generators.settings.sway.speed, 0.01..=2.0));
+                });
+                for kind in [
+                    GeneratorKind::SwayX,
+                    GeneratorKind::SwayY,
+                    GeneratorKind::SwayRoll,
+                ] {
+                    Self::value_bar(ui, generators, kind);
+                }
+            });
+    }
+
+    fn mode_select(ui: &mut Ui, id: &str, mode: &mut GeneratorMode) {
+        ui.horizontal(|ui| {
+            ui.label("Mode:");
+            ComboBox::from_id_source(("generator_mode", id))
+                .selected_text(mode.name())
+                .show_ui(ui, |ui| {
+                    for i in GeneratorMode::ALL {
+                        ui.selectable_value(mode, i, i.name());
+                    }
+                });
+        });
+    }
+
+    fn value_bar(ui: &mut Ui, generators: &Generators, kind: GeneratorKind) {
+        match generators.value(kind) {
+            Some(val) => {
+                let (min, max) = kind.range();
+                let progress = (val - min) / (max - min);
+                ui.add(ProgressBar::new(progress.clamp(0.0, 1.0)).text(format!(
+                    "{}: {:.2}",
+                    kind.name(),
+                    val
+                )));
+            }
+            None => {
+                ui.label(format!("{}: inactive", kind.name()));
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use egui::{ComboBox, Context, Grid, RichText, Ui};
+use winit::event::VirtualKeyCode;
+
+use crate::{
+    action::{Action, KeyBinding, KeyMap},
+    app::AppState,
+    hotkey::Hotkey,
+};
+
+pub struct KeyBindings {
+    pub open: bool,
+    state: Arc<AppState>,
+    // The binding waiting for a key press to bind to it.
+    capturing: Option<usize>,
+    error: Option<String>,
+}
+
+impl KeyBindings {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            capturing: None,
+            error: None,
+        }
+    }
+
+    /// Binds a pressed hotkey to the binding waiting for one, returning
+    /// whether the press was used up. Escape cancels instead.
+    pub fn capture_hotkey(&mut self, hotkey: Hotkey) -> bool {
+        let Some(ind) = self.capturing.take() else {
+            return false;
+        };
+
+        if hotkey.key != VirtualKeyCode::Escape {
+            let mut keymap = self.state.actions.keymap();
+            if let Some(binding) = keymap.bindings.get_mut(ind) {
+                binding.hotkey = Some(hotkey);
+                self.error = Self::save(&keymap);
+            }
+        }
+
+        true
+    }
+
+    fn save(keymap: &KeyMap) -> Option<String> {
+        keymap
+            .save()
+            .err()
+            .map(|e| format!("Could not save key bindings: {}", e))
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        egui::Window::new("Hotkeys")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label("Click a hotkey and press the new key to rebind it. Escape cancels.");
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                let expression_hotkeys: Vec<_> = self
+                    .state
+                    .displayed
+                    .data()
+                    .expressions
+                    .lock()
+                    .unwrap()
+                    .expressions()
+                    .iter()
+                    .filter_map(|x| Some((x.hotkey?, format!("Expression: {}", x.name))))
+                    .collect();
+
+                let mut keymap = self.state.actions.keymap();
+                let before = keymap.clone();
+                let users = keymap.users(expression_hotkeys.iter().cloned());
+
+                ui.separator();
+
+                let mut to_remove = None;
+                Grid::new("key_bindings").striped(true).show(ui, |ui| {
+                    for (ind, binding) in keymap.bindings.iter_mut().enumerate() {
+                        ui.label(binding.action.name());
+
+                        let text = if self.capturing == Some(ind) {
+                            "Press a key...".to_owned()
+                        } else {
+                            binding
+                                .hotkey
+                                .map_or("Unbound".to_owned(), |x| x.to_string())
+                        };
+                        if ui.button(text).clicked() {
+                            self.capturing = Some(ind);
+                        }
+
+                        if ui.button("Run").clicked() {
+                            self.state.actions.dispatch(binding.action.clone());
+                        }
+                        if ui.button("Remove").clicked() {
+                            to_remove = Some(ind);
+                        }
+
+                        if let Some(hotkey) = binding.hotkey {
+                            conflicts(ui, &users[&hotkey], &binding.action.name());
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                if let Some(ind) = to_remove {
+                    keymap.bindings.remove(ind);
+                    self.capturing = None;
+                }
+
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_source("key_binding_add")
+                        .selected_text("Add Binding")
+                        .show_ui(ui, |ui| {
+                            for action in Action::available(&self.state) {
+                                if ui.selectable_label(false, action.name()).clicked() {
+                                    keymap.bindings.push(KeyBinding {
+                                        hotkey: None,
+                                        action,
+                                    });
+                                    self.capturing = Some(keymap.bindings.len() - 1);
+                                }
+                            }
+                        });
+
+                    if ui.button("Reset to Defaults").clicked() {
+                        *keymap = KeyMap::default();
+                        self.capturing = None;
+                    }
+                });
+
+                if !expression_hotkeys.is_empty() {
+                    ui.separator();
+                    ui.label(
+                        RichText::new("Expression hotkeys are set in the Expressions window")
+                            .italics(),
+                    );
+                    Grid::new("expression_hotkeys").show(ui, |ui| {
+                        for (hotkey, name) in &expression_hotkeys {
+                            ui.label(name);
+                            ui.label(hotkey.to_string());
+                            conflicts(ui, &users[hotkey], name);
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                if *keymap != before {
+                    self.error = Self::save(&keymap);
+                }
+            });
+    }
+}
+
+/// Warns about everything other than `own` using the same hotkey.
+fn conflicts(ui: &mut Ui, users: &[String], own: &str) {
+    let mut others: Vec<_> = users.iter().map(|x| x.as_str()).collect();
+    if let Some(ind) = others.iter().position(|x| *x == own) {
+        others.remove(ind);
+    }
+
+    if !others.is_empty() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("Also used by {}", others.join(", ")),
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use egui::{ComboBox, Context, DragValue, Grid, Slider};
+
+use crate::{
+    app::AppState,
+    mixer::{BlendMode, LayerId},
+};
+
+pub struct Mixer {
+    pub open: bool,
+    state: Arc<AppState>,
+}
+
+impl Mixer {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { open: false, state }
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        egui::Window::new("Parameter Mixer")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Layers are applied from lowest to highest priority, \
+                    each blending its parameters over the ones before it.",
+                );
+                ui.separator();
+
+                let mut mixer = self.state.displayed.mixer.lock().unwrap();
+                let mut order = LayerId::ALL;
+                order.sort_by_key(|x| mixer.layer(*x).settings.priority);
+
+                Grid::new("mixer_layers").striped(true).show(ui, |ui| {
+                    ui.label("Layer");
+                    ui.label("Priority");
+                    ui.label("Blend");
+                    ui.label("Weight");
+                    ui.label("Fade In (s)");
+                    ui.label("Fade Out (s)");
+                    ui.label("Active");
+                    ui.end_row();
+
+                    for id in order {
+                        let layer = mixer.layer_mut(id);
+                        let settings = &mut layer.settings;
+
+                        ui.checkbox(&mut settings.enabled, id.name());
+                        ui.add(DragValue::new(&mut settings.priority));
+                        ComboBox::from_id_source(("mixer_blend", id))
+                            .selected_text(settings.blend.name())
+                            .show_ui(ui, |ui| {
+                                for mode in BlendMode::ALL {
+                                    ui.selectable_value(&mut settings.blend, mode, mode.name());
+                                }
+                            });
+                        ui.add(Slider::new(&mut settings.weight, 0.0..=1.0));
+                        ui.add(
+                            DragValue::new(&mut settings.fade_in)
+                                .speed(0.05)
+                                .clamp_range(0.0..=10.0),
+                        );
+                        ui.add(
+                            DragValue::new(&mut settings.fade_out)
+                                .speed(0.05)
+                                .clamp_range(0.0..=10.0),
+                        );
+                        ui.label(format!(
+                            "{:.0}% ({} params)",
+                            layer.fade() * 100.0,
+                            layer.values().len()
+                        ));
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+}
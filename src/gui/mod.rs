@@ -1,29 +1,76 @@
 mod about;
+mod animations;
+mod audio;
+mod drop_overlay;
+mod expressions;
 mod general_settings;
+mod history;
+mod idle_motion;
+mod key_bindings;
+mod mixer;
 mod model;
+mod outfits;
+mod stage;
+mod timeline;
 mod tracker;
 
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use egui::Context;
 
-use crate::{app::AppState, displayed_model::DisplayedModel, tracker::TrackerSystem};
+use crate::{
+    action::Action,
+    app::{
+        model::{ModelData, Stored},
+        AppState,
+    },
+    hotkey::Hotkey,
+    tracker::{ConnectError, TrackerSystem},
+};
 
 use self::{
     about::About,
+    animations::Animations,
+    audio::Audio,
+    drop_overlay::DropOverlay,
+    expressions::Expressions,
     general_settings::Settings,
-    model::{ModelManager, TrackingConfig},
+    idle_motion::IdleMotion,
+    key_bindings::KeyBindings,
+    mixer::Mixer,
+    model::{Inspector, ModelManager, ModelSettings, Physics, TrackingConfig, Triggers},
+    outfits::Outfits,
+    stage::Stage,
+    timeline::Timeline,
     tracker::Tracker,
 };
 
 pub struct Gui {
+    /// Whether any of the UI is drawn, so the puppet can be shown alone.
+    pub visible: bool,
     show_about: bool,
+    state: Arc<AppState>,
     model_manager: ModelManager,
+    stage: Stage,
+    model_settings: ModelSettings,
     tracking_config: TrackingConfig,
+    triggers: Triggers,
+    mixer: Mixer,
+    idle_motion: IdleMotion,
+    physics: Physics,
+    animations: Animations,
+    expressions: Expressions,
+    outfits: Outfits,
+    inspector: Inspector,
+    key_bindings: KeyBindings,
+    timeline: Timeline,
     tracker: Tracker,
+    audio: Audio,
     settings: Settings,
-
-    state: AppState,
+    drop_overlay: DropOverlay,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
@@ -33,20 +80,78 @@ pub struct GuiResponse {
 }
 
 impl Gui {
-    pub fn new(displayed_model: Arc<DisplayedModel>) -> Self {
+    pub fn new(state: Arc<AppState>) -> Self {
         Gui {
+            visible: true,
             show_about: false,
-            model_manager: ModelManager::new(displayed_model.clone()),
-            tracking_config: TrackingConfig::new(displayed_model),
+            model_manager: ModelManager::new(state.clone()),
+            stage: Stage::new(state.clone()),
+            model_settings: ModelSettings::new(state.clone()),
+            tracking_config: TrackingConfig::new(state.clone()),
+            triggers: Triggers::new(state.clone()),
+            mixer: Mixer::new(state.clone()),
+            animations: Animations::new(state.clone()),
+            expressions: Expressions::new(state.clone()),
+            outfits: Outfits::new(state.clone()),
+            inspector: Inspector::new(state.clone()),
+            key_bindings: KeyBindings::new(state.clone()),
+            timeline: Timeline::new(state.clone()),
+            physics: Physics::new(state.clone()),
+            idle_motion: IdleMotion::default(),
             tracker: Tracker::default(),
+            audio: Audio::default(),
             settings: Settings::default(),
+            drop_overlay: DropOverlay::default(),
+            state,
+        }
+    }
+
+    /// Offers a pressed hotkey to any window waiting to bind one, returning
+    /// whether it was used.
+    pub fn capture_hotkey(&mut self, hotkey: Hotkey) -> bool {
+        self.key_bindings.capture_hotkey(hotkey) || self.expressions.capture_hotkey(hotkey)
+    }
+
+    /// Notes a file being dragged over the window, to say what dropping it
+    /// will do.
+    pub fn file_hovered(&mut self, path: PathBuf) {
+        self.drop_overlay.file_hovered(path);
+    }
+
+    /// Notes a file dropped on the window, returning whether it should be
+    /// shown rather than only loaded, going by how many models were dropped.
+    pub fn file_dropped(&mut self, path: &Path) -> bool {
+        self.drop_overlay.file_dropped(path)
+    }
 
-            state: AppState::default(),
+    /// Forgets the files dragged over the window, once they're dragged away.
+    pub fn files_left(&mut self) {
+        self.drop_overlay.clear();
+    }
+
+    /// Reports how the tracker connecting went, in the tracker window.
+    pub fn tracker_connected(&mut self, result: Result<(), ConnectError>) {
+        self.tracker.connected(result);
+    }
+
+    /// Performs the actions that belong to the GUI, returning whether the
+    /// action was one of them.
+    pub fn perform(&mut self, action: &Action) -> bool {
+        match action {
+            Action::ToggleUi => self.visible = !self.visible,
+            _ => return false,
         }
+        true
     }
 
     pub fn draw(&mut self, ctx: &Context, tracker_system: &mut TrackerSystem) -> GuiResponse {
         let data = GuiResponse::default();
+        // Files can be dropped even with the rest of the UI hidden.
+        self.drop_overlay.draw(ctx);
+        if !self.visible {
+            return data;
+        }
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.toggle_value(&mut self.show_about, "Virst");
@@ -55,15 +160,44 @@ impl Gui {
 
                 ui.toggle_value(&mut self.model_manager.open, "Model Manager");
 
+                ui.toggle_value(&mut self.stage.open, "Stage");
+
                 ui.toggle_value(&mut self.tracker.open, "Tracker Settings");
+
+                ui.toggle_value(&mut self.audio.open, "Lip Sync");
+
+                ui.separator();
+
+                if ui.button("Reset Camera").clicked() {
+                    self.state.actions.dispatch(Action::ResetCamera);
+                }
+                if ui.button("Screenshot").clicked() {
+                    self.state.actions.dispatch(Action::Screenshot);
+                }
+                if ui.button("Hide UI").clicked() {
+                    self.state.actions.dispatch(Action::ToggleUi);
+                }
             });
         });
 
         // placing windows here let's us overlap the sidepanel.
         About::draw(&mut self.show_about, ctx);
         self.model_manager.draw(ctx);
+        self.stage.draw(ctx);
+        self.model_settings.draw(ctx, tracker_system);
         self.tracking_config.draw(ctx, tracker_system);
+        self.triggers.draw(ctx, tracker_system);
+        self.mixer.draw(ctx);
+        self.idle_motion.draw(ctx, tracker_system);
+        self.physics.draw(ctx, tracker_system);
+        self.animations.draw(ctx);
+        self.expressions.draw(ctx);
+        self.outfits.draw(ctx);
+        self.inspector.draw(ctx);
+        self.key_bindings.draw(ctx);
+        self.timeline.draw(ctx);
         self.tracker.draw(ctx, tracker_system);
+        self.audio.draw(ctx, tracker_system);
         self.settings.draw(ctx);
 
         egui::SidePanel::left("left_panel")
@@ -79,16 +213,54 @@ impl Gui {
 
                     ui.separator();
 
-                    if self.model_manager.has_model() {
+                    if self.state.displayed.current_model().0.is_some() {
                         if ui.button("Model Settings").clicked() {
-                            // self.tracking_config.open = !self.tracking_config.open;
+                            self.model_settings.open = !self.model_settings.open;
                         }
 
                         if ui.button("Tracking Config").clicked() {
                             self.tracking_config.open = !self.tracking_config.open;
                         }
 
-                        if ui.button("Hotkeys").clicked() {}
+                        if ui.button("Triggers").clicked() {
+                            self.triggers.open = !self.triggers.open;
+                        }
+
+                        if ui.button("Idle Motion").clicked() {
+                            self.idle_motion.open = !self.idle_motion.open;
+                        }
+
+                        if ui.button("Secondary Motion").clicked() {
+                            self.physics.open = !self.physics.open;
+                        }
+
+                        if ui.button("Animations").clicked() {
+                            self.animations.open = !self.animations.open;
+                        }
+
+                        if ui.button("Timeline").clicked() {
+                            self.timeline.open = !self.timeline.open;
+                        }
+
+                        if ui.button("Parameter Mixer").clicked() {
+                            self.mixer.open = !self.mixer.open;
+                        }
+
+                        if ui.button("Expressions").clicked() {
+                            self.expressions.open = !self.expressions.open;
+                        }
+
+                        if ui.button("Outfits").clicked() {
+                            self.outfits.open = !self.outfits.open;
+                        }
+
+                        if ui.button("Hotkeys").clicked() {
+                            self.key_bindings.open = !self.key_bindings.open;
+                        }
+
+                        if ui.button("Node Inspector").clicked() {
+                            self.inspector.open = !self.inspector.open;
+                        }
                     }
                 })
             });
@@ -96,3 +268,24 @@ impl Gui {
         data
     }
 }
+
+/// Saves a model's edited settings once `dirty` is set, waiting for drags to
+/// finish rather than writing every frame. A failure is put in `error`.
+///
+/// Windows call this every frame, open or not, so edits made just before one
+/// is closed are still saved.
+fn save_when_settled<T: Stored>(
+    ctx: &Context,
+    dirty: &mut bool,
+    data: &ModelData,
+    value: &T,
+    error: &mut Option<String>,
+) {
+    if *dirty && !ctx.input(|i| i.pointer.any_down()) {
+        *dirty = false;
+        *error = data
+            .save(value)
+            .err()
+            .map(|e| format!("Could not save {}: {}", T::NAME, e));
+    }
+}
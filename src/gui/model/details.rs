@@ -0,0 +1,208 @@
+use egui::{vec2, Grid, RichText, TextureHandle, Ui};
+use inox2d::{
+    model::Model,
+    nodes::node_data::InoxData,
+    puppet::{
+        PuppetAllowedModification, PuppetAllowedRedistribution, PuppetAllowedUsers,
+        PuppetUsageRights,
+    },
+};
+
+use crate::{
+    app::model::{InternalPuppet, PuppetKind},
+    static_puppet::StaticPuppet,
+};
+
+const THUMBNAIL_SIZE: f32 = 128.0;
+
+/// Shows everything known about a loaded model: its metadata, what it's
+/// made of, and what its rights say it can't be used for.
+pub fn show(ui: &mut Ui, puppet: &InternalPuppet, thumbnail: Option<&TextureHandle>) {
+    if let Some(texture) = thumbnail {
+        ui.add(egui::Image::new(texture).max_size(vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE)));
+    }
+
+    match puppet.kind() {
+        PuppetKind::Inochi2D(model) => show_inochi(ui, model),
+        PuppetKind::Static(puppet) => show_static(ui, puppet),
+    }
+}
+
+fn show_inochi(ui: &mut Ui, model: &Model) {
+    let meta = &model.puppet.meta;
+
+    match &meta.rights {
+        Some(rights) => {
+            for warning in rights_warnings(rights) {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", warning));
+            }
+            if rights.require_attribution {
+                ui.label(RichText::new("Attribution is required.").strong());
+            }
+        }
+        None => {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "⚠ The model doesn't say how it may be used. Check with its creators.",
+            );
+        }
+    }
+    ui.separator();
+
+    Grid::new("model_details").num_columns(2).show(ui, |ui| {
+        let fields = [
+            ("Name", &meta.name),
+            ("Artist", &meta.artist),
+            ("Rigger", &meta.rigger),
+            ("Contact", &meta.contact),
+            ("Copyright", &meta.copyright),
+            ("Reference", &meta.reference),
+        ];
+        for (label, value) in fields {
+            ui.label(label);
+            match value {
+                Some(value) => link_or_label(ui, value),
+                None => {
+                    ui.weak("Not given");
+                }
+            }
+            ui.end_row();
+        }
+
+        ui.label("License");
+        match &meta.license_url {
+            Some(url) => link_or_label(ui, url),
+            None => {
+                ui.weak("Not given");
+            }
+        }
+        ui.end_row();
+
+        ui.label("Inochi2D Version");
+        ui.label(&meta.version);
+        ui.end_row();
+
+        let nodes = model.puppet.nodes.arena.iter();
+        let parts = nodes
+            .filter(|x| matches!(x.get().data, InoxData::Part(_)))
+            .count();
+        ui.label("Parts");
+        ui.label(parts.to_string());
+        ui.end_row();
+
+        ui.label("Nodes");
+        ui.label(model.puppet.nodes.arena.count().to_string());
+        ui.end_row();
+
+        ui.label("Parameters");
+        ui.label(model.puppet.parameters.len().to_string());
+        ui.end_row();
+
+        ui.label("Textures");
+        ui.label(model.textures.len().to_string());
+        ui.end_row();
+    });
+
+    if let Some(rights) = &meta.rights {
+        ui.separator();
+        ui.heading("Usage Rights");
+        Grid::new("model_rights").num_columns(2).show(ui, |ui| {
+            ui.label("Who may use it");
+            ui.label(match rights.allowed_users {
+                PuppetAllowedUsers::OnlyAuthor => "Only the author",
+                PuppetAllowedUsers::OnlyLicensee => "Only the licensee",
+                PuppetAllowedUsers::Everyone => "Everyone",
+            });
+            ui.end_row();
+
+            ui.label("Redistribution");
+            ui.label(match rights.allow_redistribution {
+                PuppetAllowedRedistribution::Prohibited => "Not allowed",
+                PuppetAllowedRedistribution::ViralLicense => "Under the same license",
+                PuppetAllowedRedistribution::CopyleftLicense => "Under a copyleft license",
+            });
+            ui.end_row();
+
+            ui.label("Modification");
+            ui.label(match rights.allow_modification {
+                PuppetAllowedModification::Prohibited => "Not allowed",
+                PuppetAllowedModification::AllowPersonal => "For personal use",
+                PuppetAllowedModification::AllowRedistribute => "Allowed, and may be shared",
+            });
+            ui.end_row();
+
+            let flags = [
+                ("Commercial use", rights.allow_commercial),
+                ("Violent content", rights.allow_violence),
+                ("Sexual content", rights.allow_sexual),
+            ];
+            for (label, allowed) in flags {
+                ui.label(label);
+                ui.label(if allowed { "Allowed" } else { "Not allowed" });
+                ui.end_row();
+            }
+        });
+    }
+}
+
+fn show_static(ui: &mut Ui, puppet: &StaticPuppet) {
+    ui.weak("Static puppets don't carry any metadata.");
+    ui.separator();
+
+    Grid::new("model_details").num_columns(2).show(ui, |ui| {
+        ui.label("Name");
+        match &puppet.name {
+            Some(name) => {
+                ui.label(name);
+            }
+            None => {
+                ui.weak("Not given");
+            }
+        }
+        ui.end_row();
+
+        ui.label("Layers");
+        ui.label(puppet.layers.len().to_string());
+        ui.end_row();
+    });
+}
+
+/// The things a model's rights don't allow, most important first.
+fn rights_warnings(rights: &PuppetUsageRights) -> Vec<&'static str> {
+    let mut out = Vec::new();
+    match rights.allowed_users {
+        PuppetAllowedUsers::OnlyAuthor => out.push("Only the model's author may use it."),
+        PuppetAllowedUsers::OnlyLicensee => out.push("Only the model's licensee may use it."),
+        PuppetAllowedUsers::Everyone => {}
+    }
+    if !rights.allow_commercial {
+        out.push("Commercial use is not allowed.");
+    }
+    if matches!(
+        rights.allow_redistribution,
+        PuppetAllowedRedistribution::Prohibited
+    ) {
+        out.push("Redistribution is not allowed.");
+    }
+    if matches!(
+        rights.allow_modification,
+        PuppetAllowedModification::Prohibited
+    ) {
+        out.push("Modification is not allowed.");
+    }
+    if !rights.allow_violence {
+        out.push("Violent content is not allowed.");
+    }
+    if !rights.allow_sexual {
+        out.push("Sexual content is not allowed.");
+    }
+    out
+}
+
+fn link_or_label(ui: &mut Ui, text: &str) {
+    if text.starts_with("http://") || text.starts_with("https://") {
+        ui.hyperlink(text);
+    } else {
+        ui.label(text);
+    }
+}
@@ -0,0 +1,235 @@
+use std::{collections::HashSet, sync::Arc};
+
+use egui::{collapsing_header::CollapsingState, Context, Grid, Id, ScrollArea, Ui};
+use indextree::{Arena, NodeId};
+use inox2d::nodes::{
+    node::{InoxNode, InoxNodeUuid},
+    node_data::InoxData,
+};
+
+use crate::{app::AppState, parts::PartVisibility};
+
+fn node_type(data: &InoxData) -> &'static str {
+    match data {
+        InoxData::Node => "Node",
+        InoxData::Part(_) => "Part",
+        InoxData::Composite(_) => "Composite",
+        InoxData::SimplePhysics(_) => "Simple Physics",
+        InoxData::Custom(_) => "Custom",
+    }
+}
+
+/// The nodes of a puppet, for looking inside a rig that misbehaves.
+pub struct Inspector {
+    pub open: bool,
+    state: Arc<AppState>,
+    current: u32,
+    selected: Option<InoxNodeUuid>,
+    highlight: bool,
+}
+
+impl Inspector {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            current: 0,
+            selected: None,
+            highlight: true,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        let (model, current) = self.state.displayed.current_model();
+        if current != self.current {
+            self.current = current;
+            self.selected = None;
+        }
+
+        // Held apart from `self`, which the tree needs mutably.
+        let state = self.state.clone();
+        let mut open = self.open;
+        egui::Window::new("Node Inspector")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some(model) = &model else {
+                    ui.label("No Inochi2D Model Shown");
+                    return;
+                };
+                let nodes = &model.puppet.nodes;
+                let mut parts = state.displayed.parts.lock().unwrap();
+
+                // Nodes other parts are masked by.
+                let masks: HashSet<_> = nodes
+                    .arena
+                    .iter()
+                    .filter_map(|x| match &x.get().data {
+                        InoxData::Part(part) => part.draw_state.masks.as_ref(),
+                        _ => None,
+                    })
+                    .flat_map(|x| x.masks.iter().map(|mask| mask.source))
+                    .collect();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Show All").clicked() {
+                        parts.show_all();
+                    }
+                    ui.checkbox(&mut self.highlight, "Highlight Selected");
+                });
+                ui.separator();
+
+                ScrollArea::vertical()
+                    .id_source("node_tree")
+                    .max_height(400.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for child in nodes.root.children(&nodes.arena) {
+                            self.node_tree(ui, &nodes.arena, child, &masks, &mut parts);
+                        }
+                    });
+
+                let selected = self
+                    .selected
+                    .and_then(|uuid| nodes.arena.iter().map(|x| x.get()).find(|x| x.uuid == uuid));
+                if let Some(node) = selected {
+                    ui.separator();
+                    node_details(ui, &nodes.arena, node);
+                }
+
+                parts.set_highlighted(self.selected.filter(|_| self.highlight));
+            });
+
+        // Nothing stays highlighted once the inspector is closed.
+        if !open && self.open {
+            state.displayed.parts.lock().unwrap().set_highlighted(None);
+        }
+        self.open = open;
+    }
+
+    fn node_tree(
+        &mut self,
+        ui: &mut Ui,
+        arena: &Arena<InoxNode>,
+        id: NodeId,
+        masks: &HashSet<InoxNodeUuid>,
+        parts: &mut PartVisibility,
+    ) {
+        let node = arena[id].get();
+        let mut row = |ui: &mut Ui| {
+            let mut visible = !parts.is_hidden(node.uuid);
+            if ui.checkbox(&mut visible, "").changed() {
+                parts.set_hidden(node.uuid, !visible);
+            }
+
+            let mut text = format!("{} ({})", node.name, node_type(&node.data));
+            if masks.contains(&node.uuid) {
+                text.push_str(" (Mask)");
+            }
+            let selected = self.selected == Some(node.uuid);
+            if ui.selectable_label(selected, text).clicked() {
+                self.selected = if selected { None } else { Some(node.uuid) };
+            }
+        };
+
+        if id.children(arena).next().is_none() {
+            ui.horizontal(row);
+            return;
+        }
+
+        let state = CollapsingState::load_with_default_open(
+            ui.ctx(),
+            Id::new(("inspector_node", node.uuid.0)),
+            false,
+        );
+        state.show_header(ui, |ui| row(ui)).body(|ui| {
+            for child in id.children(arena) {
+                self.node_tree(ui, arena, child, masks, parts);
+            }
+        });
+    }
+}
+
+fn node_details(ui: &mut Ui, arena: &Arena<InoxNode>, node: &InoxNode) {
+    let name_of = |uuid: InoxNodeUuid| {
+        arena
+            .iter()
+            .map(|x| x.get())
+            .find(|x| x.uuid == uuid)
+            .map_or_else(|| format!("#{}", uuid.0), |x| x.name.clone())
+    };
+    let offset = &node.trans_offset;
+
+    Grid::new("inspector_details")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Name");
+            ui.label(&node.name);
+            ui.end_row();
+
+            ui.label("Type");
+            ui.label(node_type(&node.data));
+            ui.end_row();
+
+            ui.label("UUID");
+            ui.label(node.uuid.0.to_string());
+            ui.end_row();
+
+            ui.label("Enabled");
+            ui.label(if node.enabled { "Yes" } else { "No" });
+            ui.end_row();
+
+            ui.label("Z Sort");
+            ui.label(format!("{:.3}", node.zsort));
+            ui.end_row();
+
+            ui.label("Translation");
+            ui.label(format!(
+                "{:.1}, {:.1}, {:.1}",
+                offset.translation.x, offset.translation.y, offset.translation.z
+            ));
+            ui.end_row();
+
+            ui.label("Rotation");
+            ui.label(format!(
+                "{:.1}°, {:.1}°, {:.1}°",
+                offset.rotation.x.to_degrees(),
+                offset.rotation.y.to_degrees(),
+                offset.rotation.z.to_degrees()
+            ));
+            ui.end_row();
+
+            ui.label("Scale");
+            ui.label(format!("{:.2}, {:.2}", offset.scale.x, offset.scale.y));
+            ui.end_row();
+
+            ui.label("Lock to Root");
+            ui.label(if node.lock_to_root { "Yes" } else { "No" });
+            ui.end_row();
+
+            let draw_state = match &node.data {
+                InoxData::Part(part) => Some(&part.draw_state),
+                InoxData::Composite(composite) => Some(&composite.draw_state),
+                _ => None,
+            };
+            if let Some(draw_state) = draw_state {
+                ui.label("Opacity");
+                ui.label(format!("{:.2}", draw_state.blending.opacity));
+                ui.end_row();
+            }
+
+            if let InoxData::Part(part) = &node.data {
+                ui.label("Masked By");
+                match &part.draw_state.masks {
+                    Some(masks) if !masks.masks.is_empty() => {
+                        let sources: Vec<_> =
+                            masks.masks.iter().map(|x| name_of(x.source)).collect();
+                        ui.label(sources.join(", "));
+                    }
+                    _ => {
+                        ui.weak("Nothing");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
@@ -1,77 +1,154 @@
 use std::{
-    ffi::OsString,
+    collections::HashMap,
     fs::File,
     future::Future,
-    io::BufReader,
+    io::Read,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{Duration, Instant, SystemTime},
 };
 
-use crossbeam_channel::{bounded, Receiver, Sender};
-use egui::{Align, Context, Layout, RichText};
-use inox2d::{formats::inp::parse_inp, model::Model};
+use egui::{vec2, Align, ColorImage, Context, Layout, RichText, Sense, TextureHandle, Ui};
+use inox2d::model::Model;
 
-use crate::displayed_model::DisplayedModel;
+use crate::{
+    action::Action,
+    app::{
+        library::describe_last_used,
+        model::{InternalPuppet, PuppetKind},
+        AppState,
+    },
+    static_puppet,
+};
+
+use super::details;
+
+/// The size thumbnails are shown at in the library list.
+const THUMBNAIL_SIZE: f32 = 48.0;
+/// How often thumbnails that couldn't be found are looked for again, as
+/// they're only taken once a model has been shown for a moment.
+const THUMBNAIL_RETRY: Duration = Duration::from_secs(2);
 
 pub struct ModelManager {
     pub open: bool,
     processing: Arc<AtomicBool>,
-    displayed_model: Arc<DisplayedModel>,
+    state: Arc<AppState>,
+    // How many load failures have been seen, so new ones open the window.
+    seen_errors: usize,
+    // The library model whose details are shown.
+    details: Option<PathBuf>,
+    thumbnails: HashMap<PathBuf, Option<TextureHandle>>,
+    thumbnails_checked: Instant,
+    // The thumbnail the model whose details are shown carries itself, and
+    // which version of which model it's from.
+    own_thumbnail: Option<(PathBuf, Option<SystemTime>, Option<TextureHandle>)>,
+}
+
+fn load_thumbnail(ctx: &Context, path: &Path) -> Option<TextureHandle> {
+    decode_thumbnail(ctx, path.to_string_lossy(), File::open(path).ok()?)
+}
+
+/// The thumbnail an Inochi2D model carries among its textures, if it has
+/// one in PNG.
+fn model_thumbnail(ctx: &Context, path: &Path, model: &Model) -> Option<TextureHandle> {
+    let id = model.puppet.meta.thumbnail_id?;
+    let texture = model.textures.get(id as usize)?;
+    let name = format!("{} (own thumbnail)", path.to_string_lossy());
+    decode_thumbnail(ctx, name, &texture.data[..])
+}
+
+/// The thumbnail a model carries itself, decoded again only for another
+/// model or another version of it.
+fn own_thumbnail(
+    cache: &mut Option<(PathBuf, Option<SystemTime>, Option<TextureHandle>)>,
+    ctx: &Context,
+    puppet: &InternalPuppet,
+) -> Option<TextureHandle> {
+    let fresh = cache
+        .as_ref()
+        .is_some_and(|x| x.0 == puppet.path() && x.1 == puppet.modified());
+    if !fresh {
+        let texture = match puppet.kind() {
+            PuppetKind::Inochi2D(model) => model_thumbnail(ctx, puppet.path(), model),
+            PuppetKind::Static(_) => None,
+        };
+        *cache = Some((puppet.path().to_owned(), puppet.modified(), texture));
+    }
+    cache.as_ref()?.2.clone()
+}
 
-    models: Vec<(OsString, Model)>,
-    sender: Sender<(OsString, Model)>,
-    receiver: Receiver<(OsString, Model)>,
+fn decode_thumbnail(
+    ctx: &Context,
+    name: impl Into<String>,
+    data: impl Read,
+) -> Option<TextureHandle> {
+    let mut reader = png::Decoder::new(data).read_info().ok()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).ok()?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+
+    let image = ColorImage::from_rgba_unmultiplied(
+        [info.width as usize, info.height as usize],
+        &pixels[..info.buffer_size()],
+    );
+    Some(ctx.load_texture(name, image, Default::default()))
 }
 
 impl ModelManager {
-    pub fn new(displayed_model: Arc<DisplayedModel>) -> Self {
-        let (sender, receiver) = bounded(3);
+    pub fn new(state: Arc<AppState>) -> Self {
         Self {
             open: false,
             processing: Arc::new(AtomicBool::new(false)),
-            displayed_model,
-
-            models: Vec::new(),
-            sender,
-            receiver,
+            state,
+            seen_errors: 0,
+            details: None,
+            thumbnails: HashMap::new(),
+            thumbnails_checked: Instant::now(),
+            own_thumbnail: None,
         }
     }
 
-    pub fn has_model(&self) -> bool {
-        self.displayed_model.current_model().0.is_some()
-    }
-
     pub fn draw(&mut self, ctx: &Context) {
+        // Nothing else shows load failures, so make sure new ones are seen.
+        let errors = self.state.models().errors().len();
+        if errors > self.seen_errors {
+            self.open = true;
+        }
+        self.seen_errors = errors;
+
+        if self.thumbnails_checked.elapsed() > THUMBNAIL_RETRY {
+            self.thumbnails.retain(|_, x| x.is_some());
+            self.thumbnails_checked = Instant::now();
+        }
+
+        let mut open = self.open;
         egui::Window::new("Model Manager")
-            .open(&mut self.open)
+            .open(&mut open)
             .show(ctx, |ui| {
                 ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut egui::Ui| {
                     if ui
                         .add_enabled(
                             !self.processing.load(Ordering::SeqCst),
-                            egui::Button::new("Load Model"),
+                            egui::Button::new("Add Model"),
                         )
                         .clicked()
                     {
                         let task = rfd::AsyncFileDialog::new()
                             .add_filter("Inochi Puppet Files", &["inp"])
+                            .add_filter("Static Puppets", &static_puppet::EXTENSIONS)
                             .pick_file();
                         self.processing.store(true, Ordering::SeqCst);
 
                         let processing = self.processing.clone();
-                        let sender: Sender<(OsString, Model)> = self.sender.clone();
+                        let state = self.state.clone();
                         execute(async move {
-                            let file = task.await;
-
-                            if let Some(file) = file {
-                                // TODO: what are errors?
-                                let data = file.path();
-                                let reader = BufReader::new(File::open(data).unwrap());
-                                let model = parse_inp(reader).unwrap();
-
-                                let _ = sender.send((data.file_name().unwrap().to_owned(), model));
+                            if let Some(file) = task.await {
+                                state.models().load(file.path().to_owned());
                             }
                             processing.store(false, Ordering::SeqCst);
                         });
@@ -79,63 +156,281 @@ impl ModelManager {
 
                     if ui
                         .add_enabled(
-                            self.displayed_model.current_model().0.is_some(),
+                            self.state.displayed.current_model().0.is_some()
+                                || self.state.displayed.current_static().is_some(),
                             egui::Button::new("Remove Shown Model"),
                         )
                         .clicked()
                     {
-                        self.displayed_model.swap_model(None);
+                        self.state.hide_model();
                     }
                 });
 
-                if let Ok(data) = self.receiver.try_recv() {
-                    self.models.push(data);
+                if !self.state.models().loading().is_empty() {
+                    ui.separator();
+                    self.loading_list(ui);
                 }
 
-                let row_height = ui.spacing().interact_size.y;
+                if !self.state.models().errors().is_empty() {
+                    ui.separator();
+                    self.error_list(ui);
+                }
+
+                if !self.state.models().reloads().is_empty() {
+                    ui.separator();
+                    self.reload_list(ui);
+                }
 
-                if !self.models.is_empty() {
+                if !self.state.models().library().entries().is_empty() {
                     ui.separator();
+                    self.library_list(ui);
+                }
+            });
+        self.open = open;
+
+        self.draw_details(ctx);
+    }
 
-                    let mut to_delete: Option<usize> = None;
-                    ui.vertical(|ui| {
-                        egui::ScrollArea::vertical()
-                            .auto_shrink([false, true])
-                            .show_rows(ui, row_height, self.models.len(), |ui, row_range| {
-                                let ind_start = row_range.start;
-                                for (ind, (file, model)) in
-                                    self.models[row_range].iter().enumerate()
+    fn draw_details(&mut self, ctx: &Context) {
+        let Some(path) = self.details.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        let models = self.state.models();
+        let Some(entry) = models.library().entries().iter().find(|x| x.path == path) else {
+            self.details = None;
+            return;
+        };
+        egui::Window::new(format!("{} Details", entry.name))
+            .id(egui::Id::new("model_details"))
+            .open(&mut open)
+            .show(ctx, |ui| match models.puppet(&path) {
+                Some(puppet) => {
+                    // The captured thumbnail stands in for models without one
+                    // of their own.
+                    let thumbnail = own_thumbnail(&mut self.own_thumbnail, ui.ctx(), puppet)
+                        .or_else(|| {
+                            entry
+                                .thumbnail_path()
+                                .ok()
+                                .and_then(|x| self.thumbnails.get(&x).cloned().flatten())
+                        });
+                    details::show(ui, puppet, thumbnail.as_ref());
+                }
+                None if models.loading().iter().any(|x| x.0 == path) => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Loading...");
+                    });
+                }
+                None => {
+                    ui.label("The model has to be loaded to see its details.");
+                }
+            });
+
+        if !open {
+            self.details = None;
+        }
+    }
+
+    fn loading_list(&mut self, ui: &mut Ui) {
+        let mut models = self.state.models();
+        let mut to_cancel = None;
+        for (path, started) in models.loading() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!(
+                    "Loading {} ({:.0}s)",
+                    path.file_name()
+                        .unwrap_or(path.as_os_str())
+                        .to_string_lossy(),
+                    started.elapsed().as_secs_f32()
+                ));
+                if ui.button("Cancel").clicked() {
+                    to_cancel = Some(path.clone());
+                }
+            });
+        }
+
+        if let Some(path) = to_cancel {
+            models.cancel(&path);
+        }
+    }
+
+    fn error_list(&mut self, ui: &mut Ui) {
+        let mut models = self.state.models();
+        let mut to_dismiss = None;
+        for (ind, (path, error)) in models.errors().iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!(
+                        "Failed to load {}: {}",
+                        path.file_name()
+                            .unwrap_or(path.as_os_str())
+                            .to_string_lossy(),
+                        error
+                    ),
+                );
+                if ui.button("Dismiss").clicked() {
+                    to_dismiss = Some(ind);
+                }
+            });
+        }
+
+        if let Some(ind) = to_dismiss {
+            models.dismiss_error(ind);
+        }
+    }
+
+    fn reload_list(&mut self, ui: &mut Ui) {
+        let mut models = self.state.models();
+        let mut to_dismiss = None;
+        for (ind, reload) in models.reloads().iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Reloaded {}",
+                    reload
+                        .path
+                        .file_name()
+                        .unwrap_or(reload.path.as_os_str())
+                        .to_string_lossy()
+                ));
+                if ui.button("Dismiss").clicked() {
+                    to_dismiss = Some(ind);
+                }
+            });
+            if !reload.added.is_empty() {
+                ui.label(format!("Added parameters: {}", reload.added.join(", ")));
+            }
+            if !reload.removed.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "Removed parameters, with their bindings: {}",
+                        reload.removed.join(", ")
+                    ),
+                );
+            }
+        }
+
+        if let Some(ind) = to_dismiss {
+            models.dismiss_reload(ind);
+        }
+    }
+
+    fn library_list(&mut self, ui: &mut Ui) {
+        let mut to_show = None;
+        let mut to_remove = None;
+        let mut to_detail = None;
+        let mut changed = None;
+
+        let mut models = self.state.models();
+        let shown = models.shown().map(Path::to_owned);
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for entry in models.library().entries() {
+                    let loaded = models.is_loaded(&entry.path);
+
+                    ui.horizontal(|ui| {
+                        let thumbnail = entry.thumbnail_path().ok().and_then(|path| {
+                            self.thumbnails
+                                .entry(path.clone())
+                                .or_insert_with(|| load_thumbnail(ui.ctx(), &path))
+                                .clone()
+                        });
+                        match thumbnail {
+                            Some(texture) => {
+                                ui.add(
+                                    egui::Image::new(&texture)
+                                        .max_size(vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE)),
+                                );
+                            }
+                            None => {
+                                ui.allocate_exact_size(
+                                    vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+                                    Sense::hover(),
+                                );
+                            }
+                        }
+
+                        ui.vertical(|ui| {
+                            let is_shown = shown.as_ref() == Some(&entry.path);
+                            let name = RichText::new(&entry.name);
+                            ui.label(if is_shown { name.strong() } else { name });
+                            ui.label(
+                                RichText::new(entry.path.display().to_string())
+                                    .small()
+                                    .weak(),
+                            );
+
+                            if entry.missing {
+                                ui.colored_label(ui.visuals().error_fg_color, "File missing");
+                            } else {
+                                ui.label(
+                                    RichText::new(format!(
+                                        "Last shown {}{}",
+                                        describe_last_used(entry.last_used),
+                                        if loaded { ", loaded" } else { "" }
+                                    ))
+                                    .small(),
+                                );
+                            }
+
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(!entry.missing, egui::Button::new("Show"))
+                                    .clicked()
+                                {
+                                    to_show =
+                                        Some(entry.file_name().to_string_lossy().into_owned());
+                                }
+
+                                let mut preload = entry.settings.preload;
+                                if ui.checkbox(&mut preload, "Load on Startup").changed() {
+                                    changed = Some((entry.path.clone(), preload));
+                                }
+
+                                if ui
+                                    .add_enabled(!entry.missing, egui::Button::new("Details"))
+                                    .clicked()
                                 {
-                                    ui.horizontal_top(|ui| {
-                                        ui.label(file.to_string_lossy());
-                                        let meta = &model.puppet.meta;
-
-                                        let text = if let Some(name) = &meta.name {
-                                            RichText::new(name)
-                                        } else {
-                                            RichText::new("<unnamed>").italics()
-                                        };
-                                        ui.label(text);
-
-                                        if ui.button("Show").clicked() {
-                                            self.displayed_model.swap_model(Some(
-                                                self.models[ind + ind_start].1.clone(),
-                                            ));
-                                        }
-
-                                        if ui.button("Delete").clicked() {
-                                            to_delete = Some(ind + ind_start);
-                                        }
-                                    });
+                                    to_detail = Some(entry.path.clone());
+                                }
+
+                                if ui.button("Remove").clicked() {
+                                    to_remove = Some(entry.path.clone());
                                 }
                             });
+                        });
                     });
-
-                    if let Some(ind) = to_delete {
-                        self.models.swap_remove(ind);
-                    }
+                    ui.separator();
                 }
             });
+
+        if let Some((path, preload)) = changed {
+            models.set_preload(&path, preload);
+        }
+
+        // Details come from the model itself, so it's loaded to show them.
+        if let Some(path) = to_detail {
+            if !models.is_loaded(&path) {
+                models.load(path.clone());
+            }
+            self.details = Some(path);
+        }
+
+        if let Some(path) = to_remove {
+            models.remove(&path);
+            self.state.stage().remove_guest(&path);
+        }
+        drop(models);
+
+        if let Some(name) = to_show {
+            self.state.actions.dispatch(Action::ShowModel(name));
+        }
     }
 }
 
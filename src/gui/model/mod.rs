@@ -1,5 +1,15 @@
+mod details;
+mod inspector;
 mod manager;
+mod physics;
+mod preview;
+mod settings;
 mod tracking_config;
+mod triggers;
 
+pub use inspector::Inspector;
 pub use manager::ModelManager;
+pub use physics::Physics;
+pub use settings::ModelSettings;
 pub use tracking_config::TrackingConfig;
+pub use triggers::Triggers;
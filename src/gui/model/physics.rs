@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use egui::{Checkbox, CollapsingHeader, ComboBox, Context, DragValue, RichText};
+
+use crate::{
+    app::AppState,
+    gui::save_when_settled,
+    physics::{SourceMode, Spring, SpringSource},
+    tracker::{Axis, InputKind, TrackerSystem},
+};
+
+pub struct Physics {
+    pub open: bool,
+    state: Arc<AppState>,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl Physics {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            dirty: false,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context, tracker_system: &TrackerSystem) {
+        let (model, _) = self.state.displayed.current_model();
+
+        egui::Window::new("Secondary Motion")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                let Some(model) = &model else {
+                    ui.label("No Model Shown");
+                    return;
+                };
+
+                ui.label("Drive parameters with springs that follow another parameter or input.");
+
+                let mut params: Vec<_> = model.puppet.parameters.iter().collect();
+                params.sort_by(|a, b| a.0.cmp(b.0));
+
+                // Every parameter axis, as a spring drives or follows one at a time.
+                let axes: Vec<_> = params
+                    .iter()
+                    .flat_map(|(name, param)| {
+                        let axes: &[Axis] = if param.is_vec2 {
+                            &Axis::ALL
+                        } else {
+                            &[Axis::X]
+                        };
+                        axes.iter().map(|axis| ((*name).clone(), *axis))
+                    })
+                    .collect();
+                let sources: Vec<_> = axes
+                    .iter()
+                    .map(|(name, axis)| SpringSource::Parameter(name.clone(), *axis))
+                    .chain(
+                        InputKind::available(&tracker_system.data().lock().unwrap())
+                            .into_iter()
+                            .map(SpringSource::Input),
+                    )
+                    .collect();
+
+                let data = self.state.displayed.data();
+
+                let mut physics = data.physics.lock().unwrap();
+
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_source("spring_add")
+                        .selected_text("Add Spring")
+                        .show_ui(ui, |ui| {
+                            for (name, axis) in &axes {
+                                let text = format!("{} ({})", name, axis.name());
+                                if ui.selectable_label(false, text).clicked() {
+                                    physics.springs_mut().push(Spring::new(name.clone(), *axis));
+                                    self.dirty = true;
+                                }
+                            }
+                        });
+
+                    if ui
+                        .button("Settle")
+                        .on_hover_text("Stop every spring where it is heading")
+                        .clicked()
+                    {
+                        physics.settle();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                if physics.springs().is_empty() {
+                    ui.label(RichText::new("No springs for this model").italics());
+                }
+
+                let mut to_delete = None;
+                for ind in 0..physics.springs().len() {
+                    let position = physics.state(ind).map(|x| x.position);
+                    let spring = &mut physics.springs_mut()[ind];
+                    let before = spring.clone();
+
+                    let title = format!("{} ({})", spring.param, spring.axis.name());
+                    CollapsingHeader::new(title)
+                        .id_source(("spring", ind))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(Checkbox::new(&mut spring.enabled, "Enabled"));
+                                if let Some(position) = position {
+                                    ui.label(format!("Output: {:.3}", position));
+                                }
+                                if ui.button("Delete").clicked() {
+                                    to_delete = Some(ind);
+                                }
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Follows");
+                                ComboBox::from_id_source(("spring_source", ind))
+                                    .selected_text(spring.source.name())
+                                    .show_ui(ui, |ui| {
+                                        for source in &sources {
+                                            ui.selectable_value(
+                                                &mut spring.source,
+                                                source.clone(),
+                                                source.name(),
+                                            );
+                                        }
+                                    });
+                                ComboBox::from_id_source(("spring_mode", ind))
+                                    .selected_text(spring.mode.name())
+                                    .show_ui(ui, |ui| {
+                                        for mode in SourceMode::ALL {
+                                            ui.selectable_value(
+                                                &mut spring.mode,
+                                                mode,
+                                                mode.name(),
+                                            );
+                                        }
+                                    });
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Input Range:");
+                                ui.add(DragValue::new(&mut spring.input_range.0).speed(0.1));
+                                ui.add(DragValue::new(&mut spring.input_range.1).speed(0.1));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Output Range:");
+                                ui.add(DragValue::new(&mut spring.output_range.0).speed(0.01));
+                                ui.add(DragValue::new(&mut spring.output_range.1).speed(0.01));
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Stiffness:");
+                                ui.add(
+                                    DragValue::new(&mut spring.stiffness)
+                                        .speed(0.5)
+                                        .clamp_range(0.0..=2000.0),
+                                );
+                                ui.label("Damping:");
+                                ui.add(
+                                    DragValue::new(&mut spring.damping)
+                                        .speed(0.05)
+                                        .clamp_range(0.0..=200.0),
+                                );
+                                ui.label("Gravity:");
+                                ui.add(DragValue::new(&mut spring.gravity).speed(0.05));
+                            });
+                        });
+
+                    self.dirty |= *spring != before;
+                }
+
+                if let Some(ind) = to_delete {
+                    physics.remove(ind);
+                    self.dirty = true;
+                }
+            });
+
+        let data = self.state.displayed.data();
+        let physics = data.physics.lock().unwrap();
+        save_when_settled(ctx, &mut self.dirty, &data, &*physics, &mut self.error);
+    }
+}
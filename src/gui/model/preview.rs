@@ -0,0 +1,119 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use egui::{pos2, vec2, Color32, Sense, Shape, Stroke, Ui};
+
+/// Number of samples kept for the scrolling plot, about 3 seconds at 60 FPS.
+const PLOT_SAMPLES: usize = 180;
+const PLOT_HEIGHT: f32 = 40.0;
+
+/// How long auto-range records the input for.
+pub const AUTO_RANGE_DURATION: Duration = Duration::from_secs(5);
+
+struct AutoRange {
+    started: Instant,
+    min: f32,
+    max: f32,
+}
+
+/// Live values of a single binding axis, as shown in the binding editor.
+#[derive(Default)]
+pub struct BindingPreview {
+    samples: VecDeque<f32>,
+    auto_range: Option<AutoRange>,
+}
+
+impl BindingPreview {
+    pub fn push(&mut self, output: f32) {
+        if self.samples.len() == PLOT_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(output);
+    }
+
+    pub fn start_auto_range(&mut self) {
+        self.auto_range = Some(AutoRange {
+            started: Instant::now(),
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+        });
+    }
+
+    pub fn cancel_auto_range(&mut self) {
+        self.auto_range = None;
+    }
+
+    /// Time left on the current auto-range recording, if one is running.
+    pub fn auto_range_remaining(&self) -> Option<Duration> {
+        self.auto_range
+            .as_ref()
+            .map(|x| AUTO_RANGE_DURATION.saturating_sub(x.started.elapsed()))
+    }
+
+    /// Feeds a raw input value to auto-range, returning the recorded range
+    /// once the recording is over.
+    pub fn record_input(&mut self, input: Option<f32>) -> Option<(f32, f32)> {
+        let range = self.auto_range.as_mut()?;
+        if let Some(input) = input {
+            range.min = range.min.min(input);
+            range.max = range.max.max(input);
+        }
+
+        if range.started.elapsed() < AUTO_RANGE_DURATION {
+            return None;
+        }
+
+        let range = self.auto_range.take()?;
+        // An input that never moved can't be mapped from.
+        (range.min < range.max).then_some((range.min, range.max))
+    }
+
+    /// Draws the recent output values, scaled so the output range spans the
+    /// plot unless values fall outside of it.
+    pub fn plot(&self, ui: &mut Ui, output_range: (f32, f32)) {
+        let (rect, _) = ui.allocate_exact_size(
+            vec2(ui.available_width().min(300.0), PLOT_HEIGHT),
+            Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let (mut low, mut high) = (
+            output_range.0.min(output_range.1),
+            output_range.0.max(output_range.1),
+        );
+        for sample in &self.samples {
+            low = low.min(*sample);
+            high = high.max(*sample);
+        }
+        if high - low <= f32::EPSILON {
+            return;
+        }
+
+        let to_y = |val: f32| rect.bottom() - (val - low) / (high - low) * rect.height();
+
+        // Mark where the output range ends, which matters once values
+        // escape it.
+        let range_stroke = Stroke::new(1.0, ui.visuals().weak_text_color());
+        for bound in [output_range.0, output_range.1] {
+            let y = to_y(bound);
+            painter.line_segment([pos2(rect.left(), y), pos2(rect.right(), y)], range_stroke);
+        }
+
+        let step = rect.width() / (PLOT_SAMPLES - 1) as f32;
+        let offset = PLOT_SAMPLES - self.samples.len();
+        let points = self
+            .samples
+            .iter()
+            .enumerate()
+            .map(|(ind, val)| pos2(rect.left() + (ind + offset) as f32 * step, to_y(*val)))
+            .collect();
+
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.5, Color32::from_rgb(100, 200, 255)),
+        ));
+    }
+}
@@ -0,0 +1,444 @@
+use std::sync::Arc;
+
+use egui::{
+    pos2, vec2, Button, Color32, ComboBox, Context, DragValue, Response, RichText, Sense, Slider,
+    Stroke, TextEdit, Ui,
+};
+use glam::Vec2;
+use inox2d::model::Model;
+
+use crate::{
+    app::AppState,
+    gui::save_when_settled,
+    mixer::LayerId,
+    pose::Pose,
+    static_puppet::{StaticPuppet, MAX_TALK_THRESHOLD},
+    tracker::{InputKind, TrackerSystem},
+};
+
+const XY_PAD_SIZE: f32 = 120.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Parameters,
+    Poses,
+}
+
+impl Tab {
+    const ALL: [Tab; 2] = [Tab::Parameters, Tab::Poses];
+
+    fn name(&self) -> &str {
+        match self {
+            Tab::Parameters => "Parameters",
+            Tab::Poses => "Poses",
+        }
+    }
+}
+
+pub struct ModelSettings {
+    pub open: bool,
+    state: Arc<AppState>,
+    tab: Tab,
+
+    filter: String,
+    new_pose: String,
+    /// Seconds taken to blend into or out of a pose.
+    pose_blend: f32,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl ModelSettings {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            tab: Tab::Parameters,
+
+            filter: String::new(),
+            new_pose: String::new(),
+            pose_blend: 0.5,
+            dirty: false,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context, tracker_system: &TrackerSystem) {
+        let (model, _) = self.state.displayed.current_model();
+        let puppet = self.state.displayed.current_static();
+
+        let mut open = self.open;
+        egui::Window::new("Model Settings")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some(model) = &model else {
+                    match &puppet {
+                        Some(puppet) => self.static_settings(ui, puppet, tracker_system),
+                        None => {
+                            ui.label("No Model Shown");
+                        }
+                    }
+                    return;
+                };
+
+                ui.horizontal(|ui| {
+                    for tab in Tab::ALL {
+                        ui.selectable_value(&mut self.tab, tab, tab.name());
+                    }
+                });
+                ui.separator();
+
+                match self.tab {
+                    Tab::Parameters => self.parameters(ui, model),
+                    Tab::Poses => self.poses(ui, model),
+                }
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+        self.open = open;
+
+        // Only the shown kind of puppet has settings here to edit.
+        let data = self.state.displayed.data();
+        if model.is_some() {
+            let poses = data.poses.lock().unwrap();
+            save_when_settled(ctx, &mut self.dirty, &data, &*poses, &mut self.error);
+        } else if puppet.is_some() {
+            let overrides = data.static_settings.lock().unwrap();
+            save_when_settled(ctx, &mut self.dirty, &data, &*overrides, &mut self.error);
+        }
+    }
+
+    fn parameters(&mut self, ui: &mut Ui, model: &Model) {
+        let mut mixer = self.state.displayed.mixer.lock().unwrap();
+
+        ui.label("Pinned parameters hold their value over tracking and everything else.");
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.filter).hint_text("Filter"));
+
+            if ui.button("Unpin All").clicked() {
+                mixer.clear(LayerId::Manual);
+            }
+            if ui.button("Pin All to Defaults").clicked() {
+                for (name, param) in &model.puppet.parameters {
+                    mixer.set(LayerId::Manual, name, param.defaults);
+                }
+            }
+        });
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        let mut params: Vec<_> = model
+            .puppet
+            .parameters
+            .iter()
+            .filter(|x| x.0.to_lowercase().contains(&filter))
+            .collect();
+        params.sort_by(|a, b| a.0.cmp(b.0));
+
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for (name, param) in params {
+                    let pinned = mixer.get(LayerId::Manual, name);
+                    let current = mixer.output().get(name).copied();
+                    let mut value = pinned.or(current).unwrap_or(param.defaults);
+
+                    let mut is_pinned = pinned.is_some();
+                    let mut changed = false;
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(name).strong());
+                        if param.is_vec2 {
+                            ui.label(format!("({:.2}, {:.2})", value.x, value.y));
+                        } else {
+                            ui.label(format!("{:.2}", value.x));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if param.is_vec2 {
+                            changed |= xy_pad(ui, &mut value, param.min, param.max).changed();
+                        } else {
+                            changed |= ui
+                                .add(Slider::new(&mut value.x, param.min.x..=param.max.x))
+                                .changed();
+                        }
+
+                        ui.checkbox(&mut is_pinned, "Pin");
+                        if ui
+                            .button("Reset")
+                            .on_hover_text("Unpins the parameter, handing it back to tracking")
+                            .clicked()
+                        {
+                            is_pinned = false;
+                        }
+                        if ui.button("Pin Default").clicked() {
+                            value = param.defaults;
+                            changed = true;
+                        }
+                    });
+
+                    // Moving a parameter by hand pins it where it was left.
+                    if changed || (is_pinned && pinned.is_none()) {
+                        mixer.set(LayerId::Manual, name, value);
+                    } else if !is_pinned && pinned.is_some() {
+                        mixer.remove(LayerId::Manual, name);
+                    }
+                    ui.separator();
+                }
+            });
+    }
+
+    fn static_settings(
+        &mut self,
+        ui: &mut Ui,
+        puppet: &StaticPuppet,
+        tracker_system: &TrackerSystem,
+    ) {
+        let inputs = InputKind::available(&tracker_system.data().lock().unwrap());
+        let data = self.state.displayed.data();
+        let mut overrides = data.static_settings.lock().unwrap();
+
+        ui.horizontal(|ui| {
+            ui.label("Changes here are kept over those in the puppet's file.");
+            if ui
+                .add_enabled(overrides.is_changed(), Button::new("Use File Settings"))
+                .clicked()
+            {
+                overrides.reset();
+                self.dirty = true;
+            }
+        });
+        ui.separator();
+
+        let settings = overrides.settings(puppet).clone();
+        let mut next = settings.clone();
+
+        ui.label(RichText::new("Talking").strong());
+        ui.horizontal(|ui| {
+            ui.label("Input:");
+            ComboBox::from_id_source("static_talk_input")
+                .selected_text(next.talk_input.name())
+                .show_ui(ui, |ui| {
+                    for input in &inputs {
+                        ui.selectable_value(&mut next.talk_input, input.clone(), input.name());
+                    }
+                });
+            ui.label("Threshold:");
+            ui.add(Slider::new(
+                &mut next.talk_threshold,
+                0.0..=MAX_TALK_THRESHOLD,
+            ));
+        });
+
+        ui.label(RichText::new("Blinking").strong());
+        ui.horizontal(|ui| {
+            ui.label("Input:");
+            let selected = next
+                .blink_input
+                .as_ref()
+                .map_or("Timed".to_owned(), |x| x.name());
+            ComboBox::from_id_source("static_blink_input")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut next.blink_input, None, "Timed");
+                    for input in &inputs {
+                        ui.selectable_value(
+                            &mut next.blink_input,
+                            Some(input.clone()),
+                            input.name(),
+                        );
+                    }
+                });
+            if next.blink_input.is_some() {
+                ui.label("Threshold:");
+                ui.add(DragValue::new(&mut next.blink_threshold).speed(0.01));
+            }
+        });
+        ui.horizontal(|ui| {
+            if next.blink_input.is_none() {
+                ui.label("Every:");
+                ui.add(
+                    DragValue::new(&mut next.blink_interval)
+                        .speed(0.05)
+                        .clamp_range(0.1..=60.0)
+                        .suffix("s"),
+                );
+            }
+            ui.label("Lasting:");
+            ui.add(
+                DragValue::new(&mut next.blink_duration)
+                    .speed(0.01)
+                    .clamp_range(0.0..=5.0)
+                    .suffix("s"),
+            );
+        });
+
+        ui.label(RichText::new("Motion").strong());
+        ui.horizontal(|ui| {
+            ui.label("Bounce:");
+            ui.add(
+                DragValue::new(&mut next.bounce)
+                    .speed(0.5)
+                    .clamp_range(0.0..=1000.0)
+                    .suffix("px"),
+            );
+            ui.label("Hops per Second:");
+            ui.add(
+                DragValue::new(&mut next.bounce_speed)
+                    .speed(0.05)
+                    .clamp_range(0.0..=30.0),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Shake:");
+            ui.add(
+                DragValue::new(&mut next.shake)
+                    .speed(0.5)
+                    .clamp_range(0.0..=1000.0)
+                    .suffix("px"),
+            );
+        });
+
+        // Only changing something sets the puppet's settings aside.
+        if next != settings {
+            *overrides.settings_mut(puppet) = next;
+            self.dirty = true;
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn poses(&mut self, ui: &mut Ui, model: &Model) {
+        let data = self.state.displayed.data();
+        let mut poses = data.poses.lock().unwrap();
+
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.new_pose).hint_text("Pose name"));
+
+            let name = self.new_pose.trim();
+            let valid = !name.is_empty() && poses.pose(name).is_none();
+            if ui.add_enabled(valid, Button::new("Capture Pose")).clicked() {
+                let mixer = self.state.displayed.mixer.lock().unwrap();
+                let pose = Pose::capture(name.to_owned(), &model.puppet, &mixer);
+                poses.poses_mut().push(pose);
+                self.new_pose.clear();
+                self.dirty = true;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Blend Time:");
+            ui.add(
+                DragValue::new(&mut self.pose_blend)
+                    .speed(0.01)
+                    .clamp_range(0.0..=10.0)
+                    .suffix("s"),
+            );
+
+            let applied = poses.applied().is_some();
+            if ui.add_enabled(applied, Button::new("Release")).clicked() {
+                poses.release(self.pose_blend);
+            }
+        });
+
+        ui.separator();
+
+        if poses.poses().is_empty() {
+            ui.label(RichText::new("No poses for this model").italics());
+        }
+
+        let mut to_delete = None;
+        let names: Vec<_> = poses.poses().iter().map(|x| x.name.clone()).collect();
+        for (ind, name) in names.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let applied = poses.applied() == Some(name.as_str());
+                let text = RichText::new(name).strong();
+                ui.label(if applied {
+                    text.color(ui.visuals().selection.bg_fill)
+                } else {
+                    text
+                });
+
+                if ui.button("Apply").clicked() {
+                    poses.apply(name, self.pose_blend);
+                }
+                if ui.button("Apply Instantly").clicked() {
+                    poses.apply(name, 0.0);
+                }
+                if ui
+                    .button("Recapture")
+                    .on_hover_text("Replace the pose with the current parameter values")
+                    .clicked()
+                {
+                    let mixer = self.state.displayed.mixer.lock().unwrap();
+                    poses.poses_mut()[ind] = Pose::capture(name.clone(), &model.puppet, &mixer);
+                    self.dirty = true;
+                }
+                if ui.button("Delete").clicked() {
+                    to_delete = Some(ind);
+                }
+            });
+        }
+
+        if let Some(ind) = to_delete {
+            let pose = poses.poses_mut().remove(ind);
+            if poses.applied() == Some(pose.name.as_str()) {
+                poses.release(self.pose_blend);
+            }
+            self.dirty = true;
+        }
+    }
+}
+
+/// A square area for dragging a 2D parameter around, with x to the right
+/// and y upwards.
+fn xy_pad(ui: &mut Ui, value: &mut Vec2, min: Vec2, max: Vec2) -> Response {
+    let (rect, mut response) =
+        ui.allocate_exact_size(vec2(XY_PAD_SIZE, XY_PAD_SIZE), Sense::click_and_drag());
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    // A parameter with no range would otherwise divide by zero.
+    let range = (max - min).max(Vec2::splat(f32::EPSILON));
+    let to_screen = |val: Vec2| {
+        let t = ((val - min) / range).clamp(Vec2::ZERO, Vec2::ONE);
+        pos2(
+            rect.left() + t.x * rect.width(),
+            rect.bottom() - t.y * rect.height(),
+        )
+    };
+
+    if let Some(pos) = response.interact_pointer_pos() {
+        let pos = rect.clamp(pos);
+        let t = Vec2::new(
+            (pos.x - rect.left()) / rect.width(),
+            (rect.bottom() - pos.y) / rect.height(),
+        );
+        let next = min + t * range;
+        if next != *value {
+            *value = next;
+            response.mark_changed();
+        }
+    }
+
+    // Crosshair through the middle of the range.
+    let center = to_screen((min + max) / 2.0);
+    let stroke = Stroke::new(1.0, visuals.weak_text_color());
+    painter.line_segment(
+        [pos2(rect.left(), center.y), pos2(rect.right(), center.y)],
+        stroke,
+    );
+    painter.line_segment(
+        [pos2(center.x, rect.top()), pos2(center.x, rect.bottom())],
+        stroke,
+    );
+
+    painter.circle_filled(to_screen(*value), 4.0, Color32::from_rgb(100, 200, 255));
+
+    response
+}
@@ -1,77 +1,330 @@
 use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
     hash::Hash,
     mem::{discriminant, take},
     sync::Arc,
 };
 
-use egui::{CollapsingHeader, ComboBox, Context, DragValue, Ui};
-use inox2d::{model::Model, puppet::Puppet};
+use egui::{
+    CollapsingHeader, ComboBox, Context, DragValue, Key, KeyboardShortcut, Modifiers, RichText, Ui,
+};
+use glam::Vec2;
+use inox2d::model::Model;
+
+use crate::{
+    app::AppState,
+    gui::{history::History, save_when_settled},
+    mixer::LayerId,
+    tracker::*,
+};
+
+use super::preview::BindingPreview;
+
+/// Maximum number of edits kept in the binding history.
+const HISTORY_LIMIT: usize = 100;
+
+const UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+const REDO_ALT_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
+
+#[derive(Clone, Copy, Debug)]
+enum HistoryAction {
+    Undo(usize),
+    Redo(usize),
+}
+
+/// Edits requested from a single parameter's controls, applied once the
+/// bindings are no longer being iterated.
+#[derive(Clone, Debug)]
+enum EditAction {
+    Copy(String),
+    Paste(String),
+    Mirror(String, String),
+    Remove(String),
+}
+
+/// Values applied to every selected binding by the bulk editor.
+#[derive(Clone, Copy, Debug)]
+struct BulkEdit {
+    input_range: (f32, f32),
+    output_range: (f32, f32),
+    dampen: f32,
+}
+
+/// A field the bulk editor can apply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BulkField {
+    InputRange,
+    OutputRange,
+    Smoothing,
+}
 
-use crate::{displayed_model::DisplayedModel, tracker::*};
+impl BulkField {
+    fn name(&self) -> &str {
+        match self {
+            BulkField::InputRange => "input range",
+            BulkField::OutputRange => "output range",
+            BulkField::Smoothing => "smoothing",
+        }
+    }
+}
+
+impl Default for BulkEdit {
+    fn default() -> Self {
+        Self {
+            input_range: (-30.0, 30.0),
+            output_range: (-1.0, 1.0),
+            dampen: 0.0,
+        }
+    }
+}
 
 pub struct TrackingConfig {
     pub open: bool,
-    displayed_model: Arc<DisplayedModel>,
+    state: Arc<AppState>,
     binding: ParamBindings,
+    history: History<ParamBindings>,
+    clipboard: Option<ParamBinding>,
+    selected: BTreeSet<String>,
+    mirror_negate: bool,
+    bulk: BulkEdit,
+    // One preview per axis of each parameter.
+    previews: HashMap<String, [BindingPreview; 2]>,
     current: u32,
     model: Option<Arc<Model>>,
+    dirty: bool,
+    error: Option<String>,
 }
 
-fn bindings_for_model(model: &Puppet) -> ParamBindings {
-    let mut out = ParamBindings::new();
-    for (k, v) in &model.parameters {
-        if v.is_vec2 {
-            out.insert(k.to_owned(), ParamBinding::TwoDim(None));
-        } else {
-            out.insert(k.to_owned(), ParamBinding::OneDim(None));
+fn describe_kind_change(before: &BindingKind, after: &BindingKind) -> &'static str {
+    match (before, after) {
+        (
+            BindingKind::Simple {
+                input: input_a,
+                input_range: input_range_a,
+                output_range: output_range_a,
+                dampen: dampen_a,
+                fallback: fallback_a,
+            },
+            BindingKind::Simple {
+                input: input_b,
+                input_range: input_range_b,
+                output_range: output_range_b,
+                dampen: dampen_b,
+                fallback: fallback_b,
+            },
+        ) => {
+            if input_a != input_b {
+                "Change input"
+            } else if input_range_a != input_range_b {
+                "Change input range"
+            } else if output_range_a != output_range_b {
+                "Change output range"
+            } else if dampen_a != dampen_b {
+                "Change smoothing"
+            } else if fallback_a != fallback_b {
+                "Change fallback"
+            } else {
+                "Edit binding"
+            }
         }
+        (BindingKind::Expr, BindingKind::Expr) => "Edit binding",
+        _ => "Change binding type",
+    }
+}
+
+fn describe_change(name: &str, before: &ParamBinding, after: &ParamBinding) -> String {
+    let what = match (before, after) {
+        (ParamBinding::OneDim(None), ParamBinding::OneDim(Some(_)))
+        | (ParamBinding::TwoDim(None), ParamBinding::TwoDim(Some(_))) => "Add binding",
+        (ParamBinding::OneDim(Some(_)), ParamBinding::OneDim(None))
+        | (ParamBinding::TwoDim(Some(_)), ParamBinding::TwoDim(None)) => "Remove binding",
+        (ParamBinding::OneDim(Some(a)), ParamBinding::OneDim(Some(b))) => {
+            describe_kind_change(a, b)
+        }
+        (ParamBinding::TwoDim(Some(a)), ParamBinding::TwoDim(Some(b))) => {
+            if a.0 != b.0 {
+                describe_kind_change(&a.0, &b.0)
+            } else {
+                describe_kind_change(&a.1, &b.1)
+            }
+        }
+        _ => "Edit binding",
+    };
+
+    format!("{} ({})", what, name)
+}
+
+/// Produces a history label for the difference between two sets of bindings,
+/// or `None` if they are identical.
+fn describe_changes(before: &ParamBindings, after: &ParamBindings) -> Option<String> {
+    let mut changed = before
+        .iter()
+        .filter_map(|(name, binding)| match after.get(name) {
+            Some(new) if new == binding => None,
+            Some(new) => Some((name, binding, new)),
+            None => None,
+        });
+
+    let (name, old, new) = changed.next()?;
+    let rest = changed.count();
+    if rest == 0 {
+        Some(describe_change(name, old, new))
+    } else {
+        Some(format!("Edit {} bindings", rest + 1))
     }
-    out
 }
 
 impl TrackingConfig {
-    pub fn new(displayed_model: Arc<DisplayedModel>) -> Self {
-        let (model, current) = displayed_model.current_model();
+    pub fn new(state: Arc<AppState>) -> Self {
+        let (model, current) = state.displayed.current_model();
         Self {
             open: false,
-            displayed_model,
-            binding: if let Some(model) = &model {
-                bindings_for_model(&model.puppet)
-            } else {
-                ParamBindings::new()
-            },
+            state,
+            binding: ParamBindings::new(),
+            history: History::new(HISTORY_LIMIT),
+            clipboard: None,
+            selected: BTreeSet::new(),
+            mirror_negate: false,
+            bulk: BulkEdit::default(),
+            previews: HashMap::new(),
             current,
             model,
+            dirty: false,
+            error: None,
         }
     }
 
     pub fn draw(&mut self, ctx: &Context, tracker_system: &TrackerSystem) {
-        let (model, current) = self.displayed_model.current_model();
+        let (model, current) = self.state.displayed.current_model();
         if current > self.current {
+            // The store installs each model's own bindings when it's shown.
             self.current = current;
-            self.binding = if let Some(model) = &model {
-                bindings_for_model(&model.puppet)
-            } else {
-                ParamBindings::new()
-            };
             self.model = model;
-        } else {
-            let mut locked = self.displayed_model.bindings.lock().unwrap();
-            self.binding = take(&mut locked);
+            self.history.clear();
+            self.selected.clear();
+            self.previews.clear();
+            // The previous model's bindings were saved as it was put away.
+            self.dirty = false;
+        }
+        self.binding = take(&mut self.state.displayed.bindings.lock().unwrap());
+
+        let mut action = None;
+        if self.open && self.model.is_some() && !ctx.wants_keyboard_input() {
+            action = ctx.input_mut(|i| {
+                // Redo has to be checked first, as it is a superset of undo.
+                if i.consume_shortcut(&REDO_SHORTCUT) || i.consume_shortcut(&REDO_ALT_SHORTCUT) {
+                    Some(HistoryAction::Redo(1))
+                } else if i.consume_shortcut(&UNDO_SHORTCUT) {
+                    Some(HistoryAction::Undo(1))
+                } else {
+                    None
+                }
+            });
         }
 
+        // The values the bindings computed last frame, for previewing
+        // parameters chained to others.
+        let parameters: HashMap<String, Vec2> = self
+            .state
+            .displayed
+            .mixer
+            .lock()
+            .unwrap()
+            .layer(LayerId::Tracking)
+            .values()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.value))
+            .collect();
+
+        let before = self.binding.clone();
+        self.record_auto_ranges(tracker_system, &parameters);
+        let mut edit_label = None;
+        let mut edits = Vec::new();
+
         egui::Window::new("Tracking Config")
             .open(&mut self.open)
             .show(ctx, |ui| {
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
                 if self.model.is_some() {
                     let (collapse, expand) = ui
                         .horizontal(|ui| {
                             let collapse = ui.button("Collapse All").clicked();
                             let expand = ui.button("Expand All").clicked();
+
+                            ui.separator();
+
+                            if ui
+                                .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                                .on_hover_text(ctx.format_shortcut(&UNDO_SHORTCUT))
+                                .clicked()
+                            {
+                                action = Some(HistoryAction::Undo(1));
+                            }
+                            if ui
+                                .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                                .on_hover_text(ctx.format_shortcut(&REDO_SHORTCUT))
+                                .clicked()
+                            {
+                                action = Some(HistoryAction::Redo(1));
+                            }
+
                             (collapse, expand)
                         })
                         .inner;
 
+                    CollapsingHeader::new("History")
+                        .id_source((self.current, "history"))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            if let Some(clicked) = Self::history_list(ui, &self.history) {
+                                action = Some(clicked);
+                            }
+                        });
+
+                    ui.checkbox(
+                        &mut self.mirror_negate,
+                        "Negate output range when mirroring",
+                    );
+
+                    let cyclic = EvaluationOrder::new(&self.binding).cyclic;
+                    if !cyclic.is_empty() {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!(
+                                "Chained in a cycle, so not driven: {}",
+                                cyclic.into_iter().collect::<Vec<_>>().join(", ")
+                            ),
+                        );
+                    }
+
+                    if !self.selected.is_empty() {
+                        CollapsingHeader::new(format!(
+                            "Bulk Edit ({} selected)",
+                            self.selected.len()
+                        ))
+                        .id_source((self.current, "bulk"))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            if let Some(label) = Self::bulk_edit(
+                                ui,
+                                &mut self.bulk,
+                                &self.selected,
+                                &mut self.binding,
+                            ) {
+                                edit_label = Some(label);
+                            }
+
+                            if ui.button("Clear Selection").clicked() {
+                                self.selected.clear();
+                            }
+                        });
+                    }
+
                     // If both happen at once, which should be impossible,
                     // just collapse them all.
                     let open = if collapse {
@@ -88,12 +341,16 @@ impl TrackingConfig {
                         egui::ScrollArea::vertical()
                             .auto_shrink([false, true])
                             .show(ui, |ui| {
-                                Self::binding_toggles(
+                                edits = Self::binding_toggles(
                                     ui,
                                     self.current,
                                     open,
                                     tracker_system,
+                                    &parameters,
                                     &mut self.binding,
+                                    &mut self.selected,
+                                    self.clipboard.as_ref(),
+                                    &mut self.previews,
                                 );
                             });
                     });
@@ -102,70 +359,347 @@ impl TrackingConfig {
                 }
             });
 
-        let mut locked = self.displayed_model.bindings.lock().unwrap();
+        for edit in edits {
+            if let Some(label) = self.apply_edit(edit) {
+                edit_label = Some(label);
+            }
+        }
+
+        if let Some(label) = describe_changes(&before, &self.binding) {
+            self.history.record(edit_label.unwrap_or(label), before);
+        }
+
+        match action {
+            Some(HistoryAction::Undo(count)) => {
+                for _ in 0..count {
+                    self.history.undo(&mut self.binding);
+                }
+            }
+            Some(HistoryAction::Redo(count)) => {
+                for _ in 0..count {
+                    self.history.redo(&mut self.binding);
+                }
+            }
+            None => {}
+        }
+
+        self.dirty |= self.binding != before;
+        let data = self.state.displayed.data();
+        save_when_settled(ctx, &mut self.dirty, &data, &self.binding, &mut self.error);
+
+        let mut locked = self.state.displayed.bindings.lock().unwrap();
         *locked = take(&mut self.binding);
     }
 
+    /// Feeds every running auto-range its input, whether or not its binding
+    /// is on screen, and takes up the ranges of those that finished.
+    fn record_auto_ranges(
+        &mut self,
+        tracker_system: &TrackerSystem,
+        parameters: &HashMap<String, Vec2>,
+    ) {
+        let mut previews = self.previews.values().flatten();
+        if !previews.any(|x| x.auto_range_remaining().is_some()) {
+            return;
+        }
+
+        let data = tracker_system.data().lock().unwrap();
+        let inputs = Inputs {
+            tracker: &data,
+            calibration: tracker_system.calibration(),
+            generators: tracker_system.generators(),
+            audio: tracker_system.audio(),
+            parameters,
+        };
+        for (name, binding) in &mut self.binding {
+            let Some(previews) = self.previews.get_mut(name) else {
+                continue;
+            };
+            for (kind, preview) in binding.kinds_mut().zip(previews.iter_mut()) {
+                let BindingKind::Simple {
+                    input, input_range, ..
+                } = kind
+                else {
+                    continue;
+                };
+                if let Some((min, max)) = preview.record_input(input.value(&inputs)) {
+                    // Keep the range's direction, as a reversed range is how
+                    // a mapping gets inverted.
+                    *input_range = if input_range.0 > input_range.1 {
+                        (max, min)
+                    } else {
+                        (min, max)
+                    };
+                }
+            }
+        }
+    }
+
+    /// Applies an edit, returning a history label if it changed any binding.
+    fn apply_edit(&mut self, edit: EditAction) -> Option<String> {
+        match edit {
+            EditAction::Copy(name) => {
+                self.clipboard = self.binding.get(&name).cloned();
+                None
+            }
+            EditAction::Paste(name) => {
+                let pasted = self.clipboard.clone()?;
+                let binding = self.binding.get_mut(&name)?;
+                if !binding.same_dimension(&pasted) {
+                    return None;
+                }
+
+                *binding = pasted;
+                Some(format!("Paste binding ({})", name))
+            }
+            EditAction::Mirror(from, to) => {
+                let mirrored = self.binding.get(&from)?.mirrored(self.mirror_negate);
+                let binding = self.binding.get_mut(&to)?;
+                if !binding.same_dimension(&mirrored) {
+                    return None;
+                }
+
+                *binding = mirrored;
+                Some(format!("Mirror binding ({} to {})", from, to))
+            }
+            EditAction::Remove(name) => {
+                self.binding.get_mut(&name)?.clear_binding();
+                self.selected.remove(&name);
+                Some(format!("Remove binding ({})", name))
+            }
+        }
+    }
+
+    /// Edits every selected binding at once, returning a history label if
+    /// anything was applied.
+    fn bulk_edit(
+        ui: &mut Ui,
+        bulk: &mut BulkEdit,
+        selected: &BTreeSet<String>,
+        bindings: &mut ParamBindings,
+    ) -> Option<String> {
+        let mut applied = None;
+
+        ui.label("Input Range:");
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut bulk.input_range.0).speed(0.1));
+            ui.add(DragValue::new(&mut bulk.input_range.1).speed(0.1));
+            if ui.button("Apply").clicked() {
+                applied = Some(BulkField::InputRange);
+            }
+        });
+
+        ui.label("Output Range:");
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut bulk.output_range.0).speed(0.1));
+            ui.add(DragValue::new(&mut bulk.output_range.1).speed(0.1));
+            if ui.button("Apply").clicked() {
+                applied = Some(BulkField::OutputRange);
+            }
+        });
+
+        ui.label("Smoothing:");
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut bulk.dampen)
+                    .speed(0.01)
+                    .clamp_range(0.0..=0.99),
+            );
+            if ui.button("Apply").clicked() {
+                applied = Some(BulkField::Smoothing);
+            }
+        });
+
+        let field = applied?;
+        for name in selected {
+            let Some(binding) = bindings.get_mut(name) else {
+                continue;
+            };
+
+            // Both axes of a two dimensional binding are edited together.
+            for kind in binding.kinds_mut() {
+                if let BindingKind::Simple {
+                    input_range,
+                    output_range,
+                    dampen,
+                    ..
+                } = kind
+                {
+                    match field {
+                        BulkField::InputRange => *input_range = bulk.input_range,
+                        BulkField::OutputRange => *output_range = bulk.output_range,
+                        BulkField::Smoothing => *dampen = bulk.dampen,
+                    }
+                }
+            }
+        }
+
+        Some(format!(
+            "Bulk edit {} ({} bindings)",
+            field.name(),
+            selected.len()
+        ))
+    }
+
+    /// Lists the edit history, returning the jump requested by clicking an
+    /// entry, if any.
+    fn history_list(ui: &mut Ui, history: &History<ParamBindings>) -> Option<HistoryAction> {
+        let mut action = None;
+
+        let undo: Vec<_> = history.undo_labels().collect();
+        if undo.is_empty() && !history.can_redo() {
+            ui.label(RichText::new("No edits yet").italics());
+            return None;
+        }
+
+        for (ind, label) in undo.iter().enumerate() {
+            if ui.selectable_label(false, *label).clicked() {
+                // Undo everything after this entry, keeping it applied.
+                action = Some(HistoryAction::Undo(undo.len() - ind - 1));
+            }
+        }
+
+        ui.label(RichText::new("Current").strong());
+
+        for (ind, label) in history.redo_labels().enumerate() {
+            if ui
+                .selectable_label(false, RichText::new(label).weak())
+                .clicked()
+            {
+                action = Some(HistoryAction::Redo(ind + 1));
+            }
+        }
+
+        action
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn binding_toggles(
         ui: &mut Ui,
         current: u32,
         open: Option<bool>,
         tracker_system: &TrackerSystem,
+        parameters: &HashMap<String, Vec2>,
         binding: &mut ParamBindings,
-    ) {
+        selected: &mut BTreeSet<String>,
+        clipboard: Option<&ParamBinding>,
+        previews: &mut HashMap<String, [BindingPreview; 2]>,
+    ) -> Vec<EditAction> {
         let data = tracker_system.data().lock().unwrap();
-        let mut possible_bindings = vec![InputKind::None];
-        for (k, v) in &data.blends {
-            possible_bindings.push(InputKind::Blendshape(k.clone()));
-        }
-        for (k, v) in &data.bones {
-            use InputBoneKind::*;
-            for i in [X, Y, Z, Pitch, Yaw, Roll] {
-                possible_bindings.push(InputKind::Bone(k.clone(), i));
-            }
-        }
+        let mut possible_bindings = InputKind::available(&data);
+        possible_bindings.extend(InputKind::parameters(binding));
+        let inputs = Inputs {
+            tracker: &data,
+            calibration: tracker_system.calibration(),
+            generators: tracker_system.generators(),
+            audio: tracker_system.audio(),
+            parameters,
+        };
+        // Every input that can have a value is one of these, so the previews
+        // read from this copy and the tracker isn't held up while drawing.
+        let values = InputValues {
+            values: possible_bindings
+                .iter()
+                .map(|x| (x.clone(), x.value(&inputs)))
+                .collect(),
+            generators: tracker_system.generators(),
+        };
         drop(data);
 
+        // Mirroring is only offered between parameters of the same shape.
+        let mirrors: BTreeMap<String, String> = binding
+            .iter()
+            .filter_map(|(name, param)| {
+                let other = mirror_name(name)?;
+                binding
+                    .get(&other)
+                    .filter(|x| x.same_dimension(param))
+                    .map(|_| (name.clone(), other))
+            })
+            .collect();
+
+        let mut edits = Vec::new();
         for (name, binding) in binding {
             let header = CollapsingHeader::new(name)
                 .id_source((current, name))
                 .open(open)
                 .default_open(true);
 
-            let remove = header
-                .show(ui, |ui| {
-                    if binding.is_bound() {
-                        match binding {
-                            ParamBinding::OneDim(v) => Self::one_dim_edit(
-                                ui,
-                                name,
-                                v.as_mut().unwrap(),
-                                &possible_bindings,
-                            ),
-                            ParamBinding::TwoDim(v) => Self::two_dim_edit(
-                                ui,
-                                name,
-                                v.as_mut().unwrap(),
-                                &possible_bindings,
-                            ),
+            let can_paste = clipboard.is_some_and(|x| x.same_dimension(binding));
+
+            header.show(ui, |ui| {
+                if binding.is_bound() {
+                    ui.horizontal(|ui| {
+                        let mut is_selected = selected.contains(name);
+                        if ui.checkbox(&mut is_selected, "Select").changed() {
+                            if is_selected {
+                                selected.insert(name.clone());
+                            } else {
+                                selected.remove(name);
+                            }
                         }
-                    } else {
-                        ui.horizontal(|ui| {
-                            if ui.button("Add Binding").clicked() {
-                                binding.default_binding();
+
+                        if ui.button("Copy").clicked() {
+                            edits.push(EditAction::Copy(name.clone()));
+                        }
+
+                        if ui
+                            .add_enabled(can_paste, egui::Button::new("Paste"))
+                            .clicked()
+                        {
+                            edits.push(EditAction::Paste(name.clone()));
+                        }
+
+                        if let Some(other) = mirrors.get(name) {
+                            if ui.button(format!("Mirror to {}", other)).clicked() {
+                                edits.push(EditAction::Mirror(name.clone(), other.clone()));
                             }
-                        });
+                        }
 
-                        // Cannot remove binding if it doesn't exist
-                        false
+                        if ui.button("Remove Binding").clicked() {
+                            edits.push(EditAction::Remove(name.clone()));
+                        }
+                    });
+
+                    ui.separator();
+
+                    let [preview_x, preview_y] = previews.entry(name.clone()).or_default();
+                    match binding {
+                        ParamBinding::OneDim(v) => Self::one_dim_edit(
+                            ui,
+                            name,
+                            v.as_mut().unwrap(),
+                            &possible_bindings,
+                            &values,
+                            preview_x,
+                        ),
+                        ParamBinding::TwoDim(v) => Self::two_dim_edit(
+                            ui,
+                            name,
+                            v.as_mut().unwrap(),
+                            &possible_bindings,
+                            &values,
+                            [preview_x, preview_y],
+                        ),
                     }
-                })
-                .body_returned;
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("Add Binding").clicked() {
+                            binding.default_binding();
+                        }
 
-            if let Some(true) = remove {
-                binding.clear_binding();
-            }
+                        if ui
+                            .add_enabled(can_paste, egui::Button::new("Paste"))
+                            .clicked()
+                        {
+                            edits.push(EditAction::Paste(name.clone()));
+                        }
+                    });
+                }
+            });
         }
+
+        edits
     }
 
     fn single_binding_edit<H: Hash + Copy>(
@@ -173,6 +707,8 @@ impl TrackingConfig {
         id_source: H,
         binding: &mut BindingKind,
         input_kinds: &[InputKind],
+        values: &InputValues,
+        preview: &mut BindingPreview,
     ) {
         ui.horizontal(|ui| {
             let selected = match binding {
@@ -215,6 +751,7 @@ impl TrackingConfig {
                 input_range,
                 output_range,
                 dampen,
+                fallback,
             } => {
                 ComboBox::from_id_source(id_source)
                     .width(300.0)
@@ -225,10 +762,57 @@ impl TrackingConfig {
                         }
                     });
 
+                ui.horizontal(|ui| {
+                    ui.label("Fallback:").on_hover_text(
+                        "Generator used while the input is missing or stops changing",
+                    );
+                    ComboBox::from_id_source((id_source, "fallback"))
+                        .selected_text(
+                            fallback.map_or("None".to_string(), |x| x.name().to_string()),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(fallback, None, "None");
+                            for kind in GeneratorKind::ALL {
+                                ui.selectable_value(fallback, Some(kind), kind.name());
+                            }
+                        });
+                    if fallback.is_some() && values.generators.is_untracked(input) {
+                        ui.weak("Input untracked, falling back");
+                    }
+                });
+
+                let raw = values.get(input);
+                let output = map_value(raw.unwrap_or(0.0), *input_range, *output_range);
+                preview.push(output);
+
+                ui.horizontal(|ui| {
+                    ui.label(match raw {
+                        Some(raw) => format!("Input: {:.3}", raw),
+                        None => "Input: <missing>".to_string(),
+                    });
+                    ui.label(format!("Output: {:.3}", output));
+                });
+                preview.plot(ui, *output_range);
+
                 ui.label("Input Range:");
                 ui.horizontal(|ui| {
                     ui.add(DragValue::new(&mut input_range.0).speed(0.1));
                     ui.add(DragValue::new(&mut input_range.1).speed(0.1));
+
+                    if let Some(remaining) = preview.auto_range_remaining() {
+                        ui.label(format!("Recording... {:.1}s", remaining.as_secs_f32()));
+                        if ui.button("Cancel").clicked() {
+                            preview.cancel_auto_range();
+                        }
+                    } else if ui
+                        .button("Auto Range")
+                        .on_hover_text(
+                            "Records the input while you move, then uses its extremes as the input range",
+                        )
+                        .clicked()
+                    {
+                        preview.start_auto_range();
+                    }
                 });
 
                 ui.label("Output Range:");
@@ -236,6 +820,11 @@ impl TrackingConfig {
                     ui.add(DragValue::new(&mut output_range.0).speed(0.1));
                     ui.add(DragValue::new(&mut output_range.1).speed(0.1));
                 });
+
+                ui.horizontal(|ui| {
+                    ui.label("Smoothing:");
+                    ui.add(DragValue::new(dampen).speed(0.01).clamp_range(0.0..=0.99));
+                });
             }
         }
     }
@@ -245,16 +834,10 @@ impl TrackingConfig {
         name: &str,
         binding: &mut BindingKind,
         input_kinds: &[InputKind],
-    ) -> bool {
-        if ui.button("Remove Binding").clicked() {
-            return true;
-        }
-
-        ui.separator();
-
-        Self::single_binding_edit(ui, name, binding, input_kinds);
-
-        false
+        values: &InputValues,
+        preview: &mut BindingPreview,
+    ) {
+        Self::single_binding_edit(ui, name, binding, input_kinds, values, preview);
     }
 
     fn two_dim_edit(
@@ -262,21 +845,42 @@ impl TrackingConfig {
         name: &str,
         bindings: &mut (BindingKind, BindingKind),
         input_kinds: &[InputKind],
-    ) -> bool {
-        if ui.button("Remove Binding").clicked() {
-            return true;
-        }
-
-        ui.separator();
-
+        values: &InputValues,
+        [preview_x, preview_y]: [&mut BindingPreview; 2],
+    ) {
         ui.label("X Binding");
-        Self::single_binding_edit(ui, (name, "X"), &mut bindings.0, input_kinds);
+        Self::single_binding_edit(
+            ui,
+            (name, "X"),
+            &mut bindings.0,
+            input_kinds,
+            values,
+            preview_x,
+        );
 
         ui.separator();
 
         ui.label("Y Binding");
-        Self::single_binding_edit(ui, (name, "Y"), &mut bindings.1, input_kinds);
+        Self::single_binding_edit(
+            ui,
+            (name, "Y"),
+            &mut bindings.1,
+            input_kinds,
+            values,
+            preview_y,
+        );
+    }
+}
+
+/// The value of every input the previews can show, copied so the tracker
+/// isn't held while drawing.
+struct InputValues<'a> {
+    values: Vec<(InputKind, Option<f32>)>,
+    generators: &'a Generators,
+}
 
-        false
+impl InputValues<'_> {
+    fn get(&self, input: &InputKind) -> Option<f32> {
+        self.values.iter().find(|x| x.0 == *input).and_then(|x| x.1)
     }
 }
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use egui::{Checkbox, CollapsingHeader, ComboBox, Context, DragValue, ProgressBar, RichText, Ui};
+
+use crate::{
+    action::Action,
+    app::AppState,
+    gui::save_when_settled,
+    tracker::{Comparison, InputKind, TrackerSystem, Trigger, TriggerState},
+};
+
+/// How long a trigger is shown as having fired, in seconds.
+const FIRED_HIGHLIGHT: f32 = 0.5;
+
+pub struct Triggers {
+    pub open: bool,
+    state: Arc<AppState>,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl Triggers {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            dirty: false,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context, tracker_system: &TrackerSystem) {
+        egui::Window::new("Triggers")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label("Fire an action when a tracker input passes a threshold.");
+
+                let inputs = InputKind::available(&tracker_system.data().lock().unwrap());
+                let actions = Action::available(&self.state);
+                let data = self.state.displayed.data();
+                let mut triggers = data.triggers.lock().unwrap();
+
+                ComboBox::from_id_source("trigger_add")
+                    .selected_text("Add Trigger")
+                    .show_ui(ui, |ui| {
+                        for action in &actions {
+                            if ui.selectable_label(false, action.name()).clicked() {
+                                triggers.triggers_mut().push(Trigger::new(action.clone()));
+                                self.dirty = true;
+                            }
+                        }
+                    });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                if triggers.triggers().is_empty() {
+                    ui.label(RichText::new("No triggers for this model").italics());
+                }
+
+                let mut to_delete = None;
+                for ind in 0..triggers.triggers().len() {
+                    let state = triggers.state(ind).cloned().unwrap_or_default();
+                    let trigger = &mut triggers.triggers_mut()[ind];
+                    let before = trigger.clone();
+
+                    let title = format!("{} → {}", trigger.input.name(), trigger.action.name());
+                    CollapsingHeader::new(title)
+                        .id_source(("trigger", ind))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(Checkbox::new(&mut trigger.enabled, "Enabled"));
+                                Self::status(ui, trigger, &state);
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("When");
+                                ComboBox::from_id_source(("trigger_input", ind))
+                                    .selected_text(trigger.input.name())
+                                    .show_ui(ui, |ui| {
+                                        for input in &inputs {
+                                            ui.selectable_value(
+                                                &mut trigger.input,
+                                                input.clone(),
+                                                input.name(),
+                                            );
+                                        }
+                                    });
+
+                                ui.label("is");
+                                ComboBox::from_id_source(("trigger_comparison", ind))
+                                    .selected_text(trigger.comparison.name())
+                                    .show_ui(ui, |ui| {
+                                        for comparison in Comparison::ALL {
+                                            ui.selectable_value(
+                                                &mut trigger.comparison,
+                                                comparison,
+                                                comparison.name(),
+                                            );
+                                        }
+                                    });
+                                ui.add(DragValue::new(&mut trigger.threshold).speed(0.01));
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Hysteresis:");
+                                ui.add(
+                                    DragValue::new(&mut trigger.hysteresis)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=f32::MAX),
+                                );
+                                ui.label("Hold:");
+                                ui.add(
+                                    DragValue::new(&mut trigger.hold)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=10.0)
+                                        .suffix("s"),
+                                );
+                                ui.label("Cooldown:");
+                                ui.add(
+                                    DragValue::new(&mut trigger.cooldown)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=600.0)
+                                        .suffix("s"),
+                                );
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Then");
+                                ComboBox::from_id_source(("trigger_action", ind))
+                                    .selected_text(trigger.action.name())
+                                    .show_ui(ui, |ui| {
+                                        for action in &actions {
+                                            ui.selectable_value(
+                                                &mut trigger.action,
+                                                action.clone(),
+                                                action.name(),
+                                            );
+                                        }
+                                    });
+
+                                if ui.button("Test").clicked() {
+                                    self.state.actions.dispatch(trigger.action.clone());
+                                }
+                                if ui.button("Delete").clicked() {
+                                    to_delete = Some(ind);
+                                }
+                            });
+                        });
+
+                    self.dirty |= *trigger != before;
+                }
+
+                if let Some(ind) = to_delete {
+                    triggers.remove(ind);
+                    self.dirty = true;
+                }
+            });
+
+        let data = self.state.displayed.data();
+        let triggers = data.triggers.lock().unwrap();
+        save_when_settled(ctx, &mut self.dirty, &data, &*triggers, &mut self.error);
+    }
+
+    fn status(ui: &mut Ui, trigger: &Trigger, state: &TriggerState) {
+        let Some(value) = state.value else {
+            ui.label(RichText::new("No input").italics());
+            return;
+        };
+        ui.label(format!("{:.2}", value));
+
+        if state.since_fired.is_some_and(|x| x < FIRED_HIGHLIGHT) {
+            ui.colored_label(ui.visuals().warn_fg_color, "Fired!");
+        } else if state.cooldown_left > 0.0 {
+            ui.label(format!("Cooling down ({:.1}s)", state.cooldown_left));
+        } else if !state.armed {
+            ui.label("Waiting for release");
+        } else if state.held_for > 0.0 {
+            let progress = if trigger.hold > 0.0 {
+                state.held_for / trigger.hold
+            } else {
+                1.0
+            };
+            ui.add(ProgressBar::new(progress.min(1.0)).desired_width(80.0));
+        } else {
+            ui.label("Ready");
+        }
+    }
+}
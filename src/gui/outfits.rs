@@ -0,0 +1,180 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use egui::{Button, ComboBox, Context, DragValue, ProgressBar, RichText, TextEdit, Ui};
+
+use crate::{app::AppState, gui::save_when_settled, outfit::Outfit};
+
+pub struct Outfits {
+    pub open: bool,
+    state: Arc<AppState>,
+    new_name: String,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl Outfits {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            new_name: String::new(),
+            dirty: false,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        let (model, _) = self.state.displayed.current_model();
+
+        egui::Window::new("Outfits")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                let Some(model) = &model else {
+                    ui.label("No Model Shown");
+                    return;
+                };
+
+                let data = self.state.displayed.data();
+
+                let mut player = data.outfits.lock().unwrap();
+
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(&mut self.new_name).hint_text("Outfit name"));
+
+                    let name = self.new_name.trim();
+                    let valid = !name.is_empty() && player.outfit(name).is_none();
+                    if ui.add_enabled(valid, Button::new("New Outfit")).clicked() {
+                        player.outfits_mut().push(Outfit::new(name.to_owned()));
+                        self.new_name.clear();
+                        self.dirty = true;
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                if player.outfits().is_empty() {
+                    ui.label(RichText::new("No outfits for this model").italics());
+                }
+
+                // Every node in tree order, as any of them can be shown or
+                // hidden along with what's under it.
+                let arena = &model.puppet.nodes.arena;
+                let nodes: Vec<_> = model
+                    .puppet
+                    .nodes
+                    .root
+                    .descendants(arena)
+                    .skip(1)
+                    .map(|x| {
+                        let node = arena[x].get();
+                        (node.uuid.0, node.name.clone())
+                    })
+                    .collect();
+
+                let mut to_delete = None;
+                let names: Vec<_> = player.outfits().iter().map(|x| x.name.clone()).collect();
+                for (ind, name) in names.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(name).strong());
+
+                        let mut active = player.is_active(name);
+                        if ui.toggle_value(&mut active, "On").changed() {
+                            player.set_active(name, active);
+                        }
+                        ui.add(ProgressBar::new(player.weight(name)).desired_width(80.0));
+
+                        if ui.button("Delete").clicked() {
+                            to_delete = Some(ind);
+                        }
+                    });
+
+                    let outfit = &mut player.outfits_mut()[ind];
+                    let before = outfit.clone();
+
+                    egui::CollapsingHeader::new("Settings")
+                        .id_source(("outfit_settings", name))
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Exclusive Group:");
+                                ui.add(
+                                    TextEdit::singleline(&mut outfit.group)
+                                        .hint_text("None")
+                                        .desired_width(100.0),
+                                );
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Cross-fade:");
+                                ui.add(
+                                    DragValue::new(&mut outfit.fade)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=10.0)
+                                        .suffix("s"),
+                                );
+                            });
+
+                            ui.separator();
+                            ui.label("Shows:");
+                            node_list(ui, ("outfit_show", name), &mut outfit.show, &nodes);
+
+                            ui.separator();
+                            ui.label("Hides:");
+                            node_list(ui, ("outfit_hide", name), &mut outfit.hide, &nodes);
+                        });
+
+                    self.dirty |= *outfit != before;
+                    ui.separator();
+                }
+
+                if let Some(ind) = to_delete {
+                    player.outfits_mut().remove(ind);
+                    self.dirty = true;
+                }
+            });
+
+        let data = self.state.displayed.data();
+        let player = data.outfits.lock().unwrap();
+        save_when_settled(ctx, &mut self.dirty, &data, &*player, &mut self.error);
+    }
+}
+
+/// Lists the nodes in a set, with a way to add any other.
+fn node_list(
+    ui: &mut Ui,
+    id: impl std::hash::Hash,
+    set: &mut BTreeSet<u32>,
+    nodes: &[(u32, String)],
+) {
+    let mut to_remove = None;
+    for uuid in set.iter() {
+        ui.horizontal(|ui| {
+            match nodes.iter().find(|x| x.0 == *uuid) {
+                Some((_, name)) => ui.label(name),
+                None => ui.label(format!("#{} (missing from model)", uuid)),
+            };
+            if ui.button("Remove").clicked() {
+                to_remove = Some(*uuid);
+            }
+        });
+    }
+    if let Some(uuid) = to_remove {
+        set.remove(&uuid);
+    }
+
+    ComboBox::from_id_source(id)
+        .selected_text("Add Part")
+        .show_ui(ui, |ui| {
+            for (uuid, name) in nodes {
+                if set.contains(uuid) {
+                    continue;
+                }
+                if ui.selectable_label(false, name).clicked() {
+                    set.insert(*uuid);
+                }
+            }
+        });
+}
@@ -0,0 +1,197 @@
+use std::{str::FromStr, sync::Arc};
+
+use egui::{ComboBox, Context, DragValue, TextEdit, Ui};
+
+use crate::app::{
+    stage::{TrackerSource, Transform},
+    AppState,
+};
+
+/// The port a guest's own tracker starts on, one above the usual VMC port.
+const DEFAULT_PORT: u16 = 39540;
+
+pub struct Stage {
+    pub open: bool,
+    state: Arc<AppState>,
+    // Port text for each guest being given a tracker of its own, kept while
+    // it's being typed.
+    port_strings: Vec<String>,
+}
+
+fn transform_controls(ui: &mut Ui, transform: &mut Transform) {
+    ui.horizontal(|ui| {
+        ui.label("Position:");
+        ui.add(DragValue::new(&mut transform.position.x).speed(10.0));
+        ui.add(DragValue::new(&mut transform.position.y).speed(10.0));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Scale:");
+        ui.add(
+            DragValue::new(&mut transform.scale)
+                .speed(0.01)
+                .clamp_range(0.01..=10.0),
+        );
+        ui.label("Rotation:");
+        ui.add(
+            DragValue::new(&mut transform.rotation)
+                .speed(1.0)
+                .clamp_range(-180.0..=180.0)
+                .suffix("°"),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut transform.flip, "Flip");
+        ui.label("Draw Order:");
+        ui.add(DragValue::new(&mut transform.z_order));
+    });
+}
+
+impl Stage {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            port_strings: Vec::new(),
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        egui::Window::new("Stage")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Show other models alongside the shown one. Guests follow their own bindings, \
+                    which can be edited by swapping them in.",
+                );
+
+                let models = self.state.models();
+                let mut stage = self.state.stage();
+
+                let mut to_add = None;
+                ComboBox::from_id_source("stage_add")
+                    .selected_text("Add Guest")
+                    .show_ui(ui, |ui| {
+                        for entry in models.library().entries() {
+                            let on_stage = models.shown() == Some(entry.path.as_path())
+                                || stage.guest(&entry.path).is_some();
+                            if entry.missing || on_stage {
+                                continue;
+                            }
+                            if ui.selectable_label(false, &entry.name).clicked() {
+                                to_add = Some(entry.path.clone());
+                            }
+                        }
+                    });
+
+                ui.separator();
+
+                ui.strong(match models.shown() {
+                    Some(path) => models
+                        .library()
+                        .entries()
+                        .iter()
+                        .find(|x| x.path == path)
+                        .map_or("Shown Model".to_owned(), |x| x.name.clone()),
+                    None => "No Model Shown".to_owned(),
+                });
+                transform_controls(ui, &mut stage.transform);
+
+                self.port_strings.resize(stage.guests.len(), String::new());
+                let mut to_focus = None;
+                let mut to_remove = None;
+                for (ind, guest) in stage.guests.iter_mut().enumerate() {
+                    ui.separator();
+                    ui.push_id(ind, |ui| {
+                        let name = models
+                            .library()
+                            .entries()
+                            .iter()
+                            .find(|x| x.path == guest.path)
+                            .map_or_else(|| guest.path.display().to_string(), |x| x.name.clone());
+                        ui.horizontal(|ui| {
+                            ui.strong(name);
+                            if !models.is_loaded(&guest.path) {
+                                ui.spinner();
+                            }
+                        });
+
+                        transform_controls(ui, &mut guest.transform);
+
+                        ui.horizontal(|ui| {
+                            ui.label("Tracker:");
+                            let mut own = matches!(guest.tracker, TrackerSource::Port(_));
+                            ComboBox::from_id_source("guest_tracker")
+                                .selected_text(if own { "Own Port" } else { "Shared" })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut own, false, "Shared");
+                                    ui.selectable_value(&mut own, true, "Own Port");
+                                });
+
+                            let port_string = &mut self.port_strings[ind];
+                            if own {
+                                if guest.tracker == TrackerSource::Shared {
+                                    guest.tracker = TrackerSource::Port(DEFAULT_PORT);
+                                }
+                                if let TrackerSource::Port(port) = guest.tracker {
+                                    if port_string.is_empty() {
+                                        *port_string = port.to_string();
+                                    }
+                                }
+                                let response = ui.add(
+                                    TextEdit::singleline(port_string)
+                                        .hint_text(DEFAULT_PORT.to_string())
+                                        .desired_width(60.0),
+                                );
+                                // Only a finished port is listened on, rather than
+                                // each one passed through while typing.
+                                if response.lost_focus() {
+                                    match u16::from_str(port_string) {
+                                        Ok(port) => guest.tracker = TrackerSource::Port(port),
+                                        Err(_) => port_string.clear(),
+                                    }
+                                }
+                            } else {
+                                guest.tracker = TrackerSource::Shared;
+                                port_string.clear();
+                            }
+                        });
+                        if let (TrackerSource::Port(_), Some(error)) =
+                            (guest.tracker, &guest.tracker_error)
+                        {
+                            ui.colored_label(ui.visuals().error_fg_color, error);
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    models.is_loaded(&guest.path),
+                                    egui::Button::new("Swap In"),
+                                )
+                                .clicked()
+                            {
+                                to_focus = Some(ind);
+                            }
+                            if ui.button("Remove").clicked() {
+                                to_remove = Some(ind);
+                            }
+                        });
+                    });
+                }
+
+                drop(stage);
+                drop(models);
+
+                if let Some(path) = to_add {
+                    self.state.add_guest(path);
+                }
+                if let Some(ind) = to_focus {
+                    self.state.focus_guest(ind);
+                    self.port_strings.clear();
+                }
+                if let Some(ind) = to_remove {
+                    self.state.stage().guests.remove(ind);
+                    self.port_strings.remove(ind);
+                }
+            });
+    }
+}
@@ -0,0 +1,485 @@
+use std::sync::Arc;
+
+use egui::{
+    pos2, vec2, Color32, ComboBox, Context, DragValue, RichText, Sense, Shape, Slider, Stroke, Ui,
+};
+use glam::Vec2;
+
+use crate::{
+    animation::{Clip, Interpolation, Keyframe, Track},
+    app::AppState,
+    gui::{history::History, save_when_settled},
+};
+
+const HISTORY_LIMIT: usize = 100;
+const STRIP_HEIGHT: f32 = 18.0;
+const KEY_RADIUS: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug)]
+enum HistoryAction {
+    Undo,
+    Redo,
+}
+
+/// What happened on a track's strip this frame.
+#[derive(Default)]
+struct StripResponse {
+    select: Option<usize>,
+    scrub: Option<f32>,
+    drag: Option<(usize, f32)>,
+}
+
+pub struct Timeline {
+    pub open: bool,
+    state: Arc<AppState>,
+    current: u32,
+
+    clip: Option<String>,
+    time: f32,
+    playing: bool,
+    preview: bool,
+    // Parameter and index of the selected keyframe.
+    selected: Option<(String, usize)>,
+
+    history: History<Clip>,
+    dirty: bool,
+    error: Option<String>,
+}
+
+impl Timeline {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            open: false,
+            state,
+            current: 0,
+
+            clip: None,
+            time: 0.0,
+            playing: false,
+            preview: true,
+            selected: None,
+
+            history: History::new(HISTORY_LIMIT),
+            dirty: false,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &Context) {
+        let (model, current) = self.state.displayed.current_model();
+        if current != self.current {
+            self.current = current;
+            self.clip = None;
+            self.selected = None;
+            self.history.clear();
+        }
+
+        let data = self.state.displayed.data();
+
+        let mut player = data.animations.lock().unwrap();
+
+        // A clip can disappear from under us if it is deleted elsewhere.
+        let mut index = self
+            .clip
+            .as_ref()
+            .and_then(|name| player.clips().iter().position(|x| &x.name == name));
+        if index.is_none() {
+            self.clip = None;
+            self.selected = None;
+        }
+
+        let mut action = None;
+
+        egui::Window::new("Timeline")
+            .open(&mut self.open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                let Some(model) = &model else {
+                    ui.label("No Model Shown");
+                    return;
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label("Clip:");
+                    ComboBox::from_id_source("timeline_clip")
+                        .selected_text(self.clip.as_deref().unwrap_or("<none>"))
+                        .show_ui(ui, |ui| {
+                            for (ind, clip) in player.clips().iter().enumerate() {
+                                if ui
+                                    .selectable_label(index == Some(ind), &clip.name)
+                                    .clicked()
+                                {
+                                    index = Some(ind);
+                                    self.clip = Some(clip.name.clone());
+                                    self.selected = None;
+                                    self.time = 0.0;
+                                    self.history.clear();
+                                }
+                            }
+                        });
+
+                    ui.checkbox(&mut self.preview, "Preview on Model");
+                });
+
+                let Some(index) = index else {
+                    ui.label(
+                        RichText::new("Select a clip, or create one in the Animations window")
+                            .italics(),
+                    );
+                    return;
+                };
+
+                let clip = &mut player.clips_mut()[index];
+                let before = clip.clone();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(if self.playing { "Pause" } else { "Play" })
+                        .clicked()
+                    {
+                        self.playing = !self.playing;
+                    }
+
+                    if self.playing {
+                        self.time += ui.input(|i| i.stable_dt);
+                        if self.time > clip.length {
+                            self.time = 0.0;
+                        }
+                    }
+
+                    ui.add(
+                        Slider::new(&mut self.time, 0.0..=clip.length)
+                            .suffix("s")
+                            .text("Time"),
+                    );
+
+                    ui.separator();
+
+                    if ui
+                        .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        action = Some(HistoryAction::Undo);
+                    }
+                    if ui
+                        .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                        .clicked()
+                    {
+                        action = Some(HistoryAction::Redo);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Length:");
+                    ui.add(
+                        DragValue::new(&mut clip.length)
+                            .speed(0.05)
+                            .clamp_range(0.1..=600.0)
+                            .suffix("s"),
+                    );
+                });
+                self.time = self.time.min(clip.length);
+
+                ui.separator();
+                ui.heading("Tracks");
+
+                if clip.tracks.is_empty() {
+                    ui.label(
+                        RichText::new("Pose a parameter below to add its first keyframe").italics(),
+                    );
+                }
+
+                let length = clip.length;
+                for (param, track) in &mut clip.tracks {
+                    let selected = self
+                        .selected
+                        .as_ref()
+                        .filter(|x| &x.0 == param)
+                        .map(|x| x.1);
+
+                    let response = ui
+                        .horizontal(|ui| {
+                            ui.add_sized(
+                                [140.0, STRIP_HEIGHT],
+                                egui::Label::new(param).truncate(true),
+                            );
+                            Self::track_strip(ui, track, length, self.time, selected)
+                        })
+                        .inner;
+
+                    if let Some(time) = response.scrub {
+                        self.time = time;
+                        self.playing = false;
+                    }
+                    if let Some(ind) = response.select {
+                        self.selected = Some((param.clone(), ind));
+                    }
+                    if let Some((ind, time)) = response.drag {
+                        // Never drop a keyframe onto another one, as that
+                        // would replace it.
+                        if track.find(time).unwrap_or(ind) == ind {
+                            let ind = track.move_keyframe(ind, time);
+                            self.selected = Some((param.clone(), ind));
+                        }
+                    }
+                }
+
+                if let Some((param, ind)) = self.selected.clone() {
+                    match clip.tracks.get_mut(&param) {
+                        Some(track) if ind < track.keyframes().len() => {
+                            ui.separator();
+                            let is_vec2 = model
+                                .puppet
+                                .parameters
+                                .get(&param)
+                                .is_some_and(|x| x.is_vec2);
+                            if Self::keyframe_edit(
+                                ui,
+                                &param,
+                                track,
+                                ind,
+                                is_vec2,
+                                &mut self.selected,
+                            ) {
+                                track.remove(ind);
+                                self.selected = None;
+                            }
+                        }
+                        _ => self.selected = None,
+                    }
+                }
+
+                ui.separator();
+                ui.heading("Pose");
+                ui.label("Moving a slider keys the parameter at the current time.");
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        let mut params: Vec<_> = model.puppet.parameters.iter().collect();
+                        params.sort_by(|a, b| a.0.cmp(b.0));
+
+                        for (name, param) in params {
+                            let track = clip.tracks.get(name);
+                            let current = track
+                                .and_then(|x| x.sample(self.time))
+                                .unwrap_or(param.defaults);
+                            let keyed = track.and_then(|x| x.find(self.time));
+
+                            let mut value = current;
+                            let mut unkey = false;
+                            ui.horizontal(|ui| {
+                                ui.add_sized([140.0, 18.0], egui::Label::new(name).truncate(true));
+                                ui.add(Slider::new(&mut value.x, param.min.x..=param.max.x));
+                                if param.is_vec2 {
+                                    ui.add(Slider::new(&mut value.y, param.min.y..=param.max.y));
+                                }
+
+                                if keyed.is_some() {
+                                    unkey = ui.button("Unkey").clicked();
+                                } else if ui.button("Key").clicked() {
+                                    // Keying an unchanged value still marks
+                                    // the pose to hold at this time.
+                                    Self::key(clip, name, self.time, current);
+                                }
+                            });
+
+                            if value != current {
+                                Self::key(clip, name, self.time, value);
+                            }
+                            if unkey {
+                                if let (Some(track), Some(ind)) = (clip.tracks.get_mut(name), keyed)
+                                {
+                                    track.remove(ind);
+                                }
+                                self.selected = None;
+                            }
+                        }
+                    });
+
+                clip.tracks.retain(|_, track| !track.is_empty());
+
+                if *clip != before {
+                    self.history.record("Edit clip", before);
+                    self.dirty = true;
+                }
+            });
+
+        if let (Some(action), Some(index)) = (action, index) {
+            let clip = &mut player.clips_mut()[index];
+            let changed = match action {
+                HistoryAction::Undo => self.history.undo(clip),
+                HistoryAction::Redo => self.history.redo(clip),
+            };
+            self.dirty |= changed;
+            self.selected = None;
+        }
+
+        let preview = (self.open && self.preview)
+            .then(|| self.clip.clone().map(|x| (x, self.time)))
+            .flatten();
+        player.set_preview(preview);
+
+        save_when_settled(ctx, &mut self.dirty, &data, &*player, &mut self.error);
+
+        if let Some(error) = &self.error {
+            if self.open {
+                egui::Window::new("Timeline Error")
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    });
+            }
+        }
+    }
+
+    /// Sets a parameter's value at a time, adding a keyframe if needed.
+    fn key(clip: &mut Clip, param: &str, time: f32, value: Vec2) {
+        let track = clip.tracks.entry(param.to_owned()).or_default();
+        let interpolation = match track.find(time) {
+            Some(ind) => track.keyframes()[ind].interpolation,
+            None => Interpolation::EaseInOut,
+        };
+
+        track.insert(Keyframe {
+            time,
+            value,
+            interpolation,
+        });
+    }
+
+    /// Edits the selected keyframe, returning whether it should be deleted.
+    fn keyframe_edit(
+        ui: &mut Ui,
+        param: &str,
+        track: &mut Track,
+        ind: usize,
+        is_vec2: bool,
+        selected: &mut Option<(String, usize)>,
+    ) -> bool {
+        let keyframe = track.keyframes()[ind].clone();
+        ui.label(RichText::new(format!("Keyframe: {}", param)).strong());
+
+        let mut time = keyframe.time;
+        let mut value = keyframe.value;
+        let mut interpolation = keyframe.interpolation;
+        let mut delete = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Time:");
+            ui.add(
+                DragValue::new(&mut time)
+                    .speed(0.01)
+                    .clamp_range(0.0..=600.0)
+                    .suffix("s"),
+            );
+
+            ui.label("Value:");
+            ui.add(DragValue::new(&mut value.x).speed(0.01));
+            if is_vec2 {
+                ui.add(DragValue::new(&mut value.y).speed(0.01));
+            }
+
+            ui.label("Easing:");
+            ComboBox::from_id_source("timeline_easing")
+                .selected_text(interpolation.name())
+                .show_ui(ui, |ui| {
+                    for i in Interpolation::ALL {
+                        ui.selectable_value(&mut interpolation, i, i.name());
+                    }
+                });
+
+            delete = ui.button("Delete").clicked();
+        });
+
+        if value != keyframe.value {
+            track.set_value(ind, value);
+        }
+        if interpolation != keyframe.interpolation {
+            track.set_interpolation(ind, interpolation);
+        }
+        if time != keyframe.time && track.find(time).unwrap_or(ind) == ind {
+            let ind = track.move_keyframe(ind, time);
+            *selected = Some((param.to_owned(), ind));
+        }
+
+        delete
+    }
+
+    fn track_strip(
+        ui: &mut Ui,
+        track: &Track,
+        length: f32,
+        time: f32,
+        selected: Option<usize>,
+    ) -> StripResponse {
+        let width = ui.available_width().max(100.0);
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(width, STRIP_HEIGHT), Sense::click_and_drag());
+        let painter = ui.painter_at(rect.expand(KEY_RADIUS));
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let to_x = |t: f32| rect.left() + t / length * rect.width();
+        let to_time = |x: f32| ((x - rect.left()) / rect.width() * length).clamp(0.0, length);
+
+        let hovered_key = response.interact_pointer_pos().and_then(|pos| {
+            track
+                .keyframes()
+                .iter()
+                .position(|x| (to_x(x.time) - pos.x).abs() <= KEY_RADIUS * 1.5)
+        });
+
+        let mut out = StripResponse::default();
+        if let Some(pos) = response.interact_pointer_pos() {
+            if response.clicked() || response.drag_started() {
+                match hovered_key {
+                    Some(ind) => out.select = Some(ind),
+                    None => out.scrub = Some(to_time(pos.x)),
+                }
+
+                // Remember whether this drag grabbed a keyframe, or is just
+                // scrubbing through time.
+                let grabbed = hovered_key.is_some();
+                ui.ctx().data_mut(|d| d.insert_temp(response.id, grabbed));
+            } else if response.dragged() {
+                let grabbed = ui
+                    .ctx()
+                    .data(|d| d.get_temp::<bool>(response.id))
+                    .unwrap_or(false);
+                match selected {
+                    Some(ind) if grabbed => out.drag = Some((ind, to_time(pos.x))),
+                    _ => out.scrub = Some(to_time(pos.x)),
+                }
+            }
+        }
+
+        let playhead = to_x(time);
+        painter.line_segment(
+            [pos2(playhead, rect.top()), pos2(playhead, rect.bottom())],
+            Stroke::new(1.5, Color32::from_rgb(230, 80, 80)),
+        );
+
+        for (ind, keyframe) in track.keyframes().iter().enumerate() {
+            let center = pos2(to_x(keyframe.time), rect.center().y);
+            let fill = if selected == Some(ind) {
+                Color32::from_rgb(255, 200, 60)
+            } else {
+                ui.visuals().text_color()
+            };
+
+            painter.add(Shape::convex_polygon(
+                vec![
+                    center + vec2(0.0, -KEY_RADIUS),
+                    center + vec2(KEY_RADIUS, 0.0),
+                    center + vec2(0.0, KEY_RADIUS),
+                    center + vec2(-KEY_RADIUS, 0.0),
+                ],
+                fill,
+                Stroke::NONE,
+            ));
+        }
+
+        out
+    }
+}
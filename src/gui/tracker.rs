@@ -2,7 +2,7 @@ use std::{net::IpAddr, str::FromStr};
 
 use egui::{Button, Context, TextEdit};
 
-use crate::tracker::TrackerSystem;
+use crate::tracker::{ConnectError, TrackerSystem};
 
 #[derive(Default)]
 pub struct Tracker {
@@ -11,9 +11,20 @@ pub struct Tracker {
     ip_dirty: bool,
     port_string: String,
     port_dirty: bool,
+    // Why the tracker couldn't start listening the last time it tried.
+    error: Option<String>,
 }
 
 impl Tracker {
+    /// Notes how connecting went, opening the window to say why if it
+    /// failed.
+    pub fn connected(&mut self, result: Result<(), ConnectError>) {
+        self.error = result.err().map(|e| e.to_string());
+        if self.error.is_some() {
+            self.open = true;
+        }
+    }
+
     pub fn draw(&mut self, ctx: &Context, tracker_system: &mut TrackerSystem) {
         egui::Window::new("Tracker")
             .open(&mut self.open)
@@ -22,6 +33,25 @@ impl Tracker {
 
                 ui.separator();
 
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Calibrate")
+                        .on_hover_text("Takes the pose you're in now as looking straight ahead")
+                        .clicked()
+                    {
+                        tracker_system.calibrate();
+                    }
+                    let calibrated = !tracker_system.calibration().is_empty();
+                    if ui
+                        .add_enabled(calibrated, Button::new("Clear Calibration"))
+                        .clicked()
+                    {
+                        tracker_system.clear_calibration();
+                    }
+                });
+
+                ui.separator();
+
                 let ip_response = ui
                     .horizontal(|ui| {
                         let error = !self.ip_string.is_empty()
@@ -76,7 +106,14 @@ impl Tracker {
                     let port = u16::from_str(&self.port_string).unwrap();
                     println!("{:?} {}", ip, port);
                     tracker_system.disconnect();
-                    let _ = tracker_system.connect((ip, port));
+                    self.error = tracker_system
+                        .connect((ip, port))
+                        .err()
+                        .map(|e| e.to_string());
+                }
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
                 }
             });
     }
@@ -1,31 +1,112 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::PathBuf,
+    process::exit,
+    sync::Arc,
+    time::Instant,
+};
+
+use action::Action;
+use app::{library::THUMBNAIL_FILE, model::is_model_file, storage, AppState};
+
+use compositor::Compositor;
 
-use displayed_model::DisplayedModel;
 use egui_integration::EguiIntegration;
 use egui_wgpu::wgpu;
 use example_scene_controller::ExampleSceneController;
-use glam::{uvec2, EulerRot, Vec2};
+use glam::{uvec2, Vec2};
+use guests::Guests;
 use gui::Gui;
-use inox2d::{model::Model, puppet::Puppet};
-use inox2d_wgpu::Renderer;
-use mahou_vmc::VmcData;
-use tracker::{BindingKind, ParamBinding, ParamBindings, TrackerSystem};
+use hotkey::Hotkey;
+use inox2d::math::camera::Camera;
+use mixer::{LayerId, ParamMixer};
+use stage_puppet::StagePuppet;
+use tracker::{EvaluationOrder, Inputs, ParamBinding, ParamBindings, Smoothing, TrackerSystem};
 use winit::{
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, KeyboardInput, ModifiersState, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::WindowBuilder,
 };
 
+mod action;
+mod animation;
 mod app;
+mod audio;
+mod compositor;
 mod displayed_model;
 mod egui_integration;
 mod example_scene_controller;
+mod expression;
+mod guests;
 mod gui;
+mod hotkey;
+mod mixer;
+mod outfit;
+mod parts;
+mod physics;
+mod pose;
+mod screenshot;
+mod stage_puppet;
+mod static_puppet;
 mod tracker;
 
-pub async fn run() {
+const CAMERA_SCALE: f32 = 0.12;
+
+const USAGE: &str = "Usage: virst [--show] [MODEL]...
+
+Loads each MODEL file, adding it to the model library.
+
+Options:
+  -s, --show  Show the first MODEL once it's loaded
+  -h, --help  Print this message";
+
+/// What was asked for on the command line.
+#[derive(Default)]
+struct Args {
+    models: Vec<PathBuf>,
+    show: bool,
+    help: bool,
+}
+
+impl Args {
+    fn parse() -> Result<Args, String> {
+        let mut args = Args::default();
+        let mut flags = true;
+        for arg in env::args_os().skip(1) {
+            if flags {
+                match arg.to_str() {
+                    Some("-s" | "--show") => {
+                        args.show = true;
+                        continue;
+                    }
+                    Some("-h" | "--help") => {
+                        args.help = true;
+                        continue;
+                    }
+                    Some("--") => {
+                        flags = false;
+                        continue;
+                    }
+                    Some(flag) if flag.starts_with('-') => {
+                        return Err(format!("unknown option {}", flag));
+                    }
+                    _ => {}
+                }
+            }
+
+            // Library entries are keyed by path, so the same file given
+            // two ways is only added once.
+            let path = PathBuf::from(arg);
+            args.models.push(fs::canonicalize(&path).unwrap_or(path));
+        }
+        Ok(args)
+    }
+}
+
+async fn run(args: Args) {
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
         .with_inner_size(winit::dpi::PhysicalSize::new(1024, 1024))
@@ -75,15 +156,39 @@ pub async fn run() {
         view_formats: Vec::new(),
     };
 
-    let displayed_model = Arc::new(DisplayedModel::default());
+    let state = Arc::new(AppState::new());
+    for (ind, path) in args.models.into_iter().enumerate() {
+        state.open(path, args.show && ind == 0);
+    }
 
-    let mut current: Option<(Model, ExampleSceneController, Renderer)> = None;
+    let mut current: Option<StagePuppet> = None;
+    let mut guests = Guests::default();
+    // What guests are rendered into before being layered over the frame,
+    // kept until the frame changes size.
+    let mut scratch: Option<(wgpu::Texture, wgpu::TextureView)> = None;
+    let compositor = Compositor::new(&device, wgpu::TextureFormat::Bgra8Unorm);
+    // Every puppet on stage is seen through this camera, after being placed
+    // by its own transform.
+    let mut camera = Camera::default();
+    camera.scale = Vec2::splat(CAMERA_SCALE);
+    let mut scene_ctrl = ExampleSceneController::new(&camera, 0.5);
     let mut generation: u32 = 0;
+    // Frames left until a thumbnail is taken of a model without one, giving
+    // it time to settle into its tracked pose first.
+    let mut thumbnail_in: Option<u32> = None;
 
     let mut integration = EguiIntegration::new(&window, &device, wgpu::TextureFormat::Bgra8Unorm);
     let mut tracker_system = TrackerSystem::new();
+    let mut smoothing = Smoothing::default();
+    // Parameter values computed from bindings last frame, for inputs read
+    // outside of binding evaluation.
+    let mut parameters = HashMap::new();
+    let mut last_frame = Instant::now();
+    let mut modifiers = ModifiersState::empty();
+    // Keys currently down, so key repeats don't re-trigger hotkeys.
+    let mut held_keys = HashSet::new();
 
-    let mut gui = Gui::new(displayed_model.clone());
+    let mut gui = Gui::new(state.clone());
 
     use simple_moving_average::SMA;
     let mut ma = simple_moving_average::SumTreeSMA::<_, f64, 300>::new();
@@ -92,24 +197,76 @@ pub async fn run() {
     event_loop.run(move |event, _, control_flow| match event {
         Event::RedrawRequested(_) => {
             let now = Instant::now();
-            let next_displayed = displayed_model.current_model();
+            let dt = (now - last_frame).as_secs_f32();
+            last_frame = now;
+
+            state.update();
+
+            let mut screenshot = false;
+            for action in state.actions.drain() {
+                match action {
+                    Action::ResetCamera => {
+                        scene_ctrl.reset(&mut camera, Vec2::ZERO, Vec2::splat(CAMERA_SCALE));
+                    }
+                    Action::ResetTracking => {
+                        tracker_system.reset();
+                        smoothing.clear();
+                        parameters.clear();
+                    }
+                    Action::Calibrate => tracker_system.calibrate(),
+                    Action::ReconnectTracker => gui.tracker_connected(tracker_system.reconnect()),
+                    Action::Screenshot => screenshot = true,
+                    Action::ToggleExpression(name) => state
+                        .displayed
+                        .data()
+                        .expressions
+                        .lock()
+                        .unwrap()
+                        .toggle(&name),
+                    Action::PlayAnimation(name) => state
+                        .displayed
+                        .data()
+                        .animations
+                        .lock()
+                        .unwrap()
+                        .play(&name),
+                    Action::ToggleOutfit(name) => {
+                        state.displayed.data().outfits.lock().unwrap().toggle(&name)
+                    }
+                    Action::ShowModel(name) => state.show_named(&name),
+                    Action::NextModel => state.show_relative(1),
+                    Action::PreviousModel => state.show_relative(-1),
+                    action => {
+                        gui.perform(&action);
+                    }
+                }
+            }
+
+            let next_displayed = state.displayed.current_model();
             if next_displayed.1 > generation {
-                if let Some(next) = next_displayed.0 {
-                    let mut next_renderer = Renderer::new(
+                let size = uvec2(window.inner_size().width, window.inner_size().height);
+                current = if let Some(next) = next_displayed.0 {
+                    Some(StagePuppet::from_model(
                         &device,
                         &queue,
-                        wgpu::TextureFormat::Bgra8Unorm,
-                        &next,
-                        uvec2(window.inner_size().width, window.inner_size().height),
-                    );
-                    next_renderer.camera.scale = Vec2::splat(0.12);
-
-                    let next_controller = ExampleSceneController::new(&next_renderer.camera, 0.5);
-
-                    current = Some((next.as_ref().clone(), next_controller, next_renderer));
+                        next.as_ref().clone(),
+                        size,
+                    ))
                 } else {
-                    current = None;
-                }
+                    state.displayed.current_static().map(|next| {
+                        StagePuppet::from_static(&device, &queue, next.as_ref().clone(), size)
+                    })
+                };
+                thumbnail_in = state
+                    .displayed
+                    .current_name()
+                    .and_then(|name| storage::model_file(&name, THUMBNAIL_FILE).ok())
+                    .filter(|path| !path.exists())
+                    .map(|_| 30);
+                smoothing.clear();
+                parameters.clear();
+                state.displayed.mixer.lock().unwrap().reset();
+                state.displayed.parts.lock().unwrap().reset();
                 generation = next_displayed.1;
             }
 
@@ -143,15 +300,141 @@ pub async fn run() {
 
             let temp_view = temp_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-            if let Some((model, scene_ctrl, renderer)) = &mut current {
-                apply_bindings(
-                    &mut model.puppet,
-                    &displayed_model.bindings.lock().unwrap(),
+            tracker_system.update(dt);
+
+            if let Some(StagePuppet::Inochi2D(model, _)) = &mut current {
+                let stored = state.displayed.data();
+                {
+                    let data = tracker_system.data().lock().unwrap();
+                    let inputs = Inputs {
+                        tracker: &data,
+                        calibration: tracker_system.calibration(),
+                        generators: tracker_system.generators(),
+                        audio: tracker_system.audio(),
+                        parameters: &parameters,
+                    };
+                    stored
+                        .triggers
+                        .lock()
+                        .unwrap()
+                        .update(dt, &inputs, &state.actions);
+                }
+
+                let mut mixer = state.displayed.mixer.lock().unwrap();
+                parameters = apply_bindings(
+                    &mut mixer,
+                    &state.displayed.bindings.lock().unwrap(),
                     &tracker_system,
+                    &mut smoothing,
+                    dt,
+                );
+                stored.animations.lock().unwrap().update(dt, &mut mixer);
+                stored.expressions.lock().unwrap().update(dt, &mut mixer);
+                stored.poses.lock().unwrap().update(dt, &mut mixer);
+                {
+                    let data = tracker_system.data().lock().unwrap();
+                    let inputs = Inputs {
+                        tracker: &data,
+                        calibration: tracker_system.calibration(),
+                        generators: tracker_system.generators(),
+                        audio: tracker_system.audio(),
+                        parameters: &parameters,
+                    };
+                    stored
+                        .physics
+                        .lock()
+                        .unwrap()
+                        .update(dt, &inputs, &mut mixer);
+                }
+                mixer.apply(&mut model.puppet, dt);
+                drop(mixer);
+                let mut outfits = stored.outfits.lock().unwrap();
+                outfits.update(dt);
+                state
+                    .displayed
+                    .parts
+                    .lock()
+                    .unwrap()
+                    .apply(&mut model.puppet, &outfits.visibility());
+            } else if let Some(puppet) = &mut current {
+                let data = tracker_system.data().lock().unwrap();
+                let inputs = Inputs {
+                    tracker: &data,
+                    calibration: tracker_system.calibration(),
+                    generators: tracker_system.generators(),
+                    audio: tracker_system.audio(),
+                    parameters: &parameters,
+                };
+                let stored = state.displayed.data();
+                let overrides = stored.static_settings.lock().unwrap();
+                puppet.update_static(dt, &inputs, &overrides);
+            }
+
+            let stage = state.stage().clone();
+            {
+                let models = state.models();
+                guests.sync(
+                    &stage,
+                    &models,
+                    &device,
+                    &queue,
+                    uvec2(window.inner_size().width, window.inner_size().height),
                 );
+                guests.update(dt, &models, &tracker_system);
+            }
+            for guest in &mut state.stage().guests {
+                guest.tracker_error = guests.tracker_error(&guest.path);
+            }
 
-                scene_ctrl.update(&mut renderer.camera);
-                renderer.render(&queue, &device, &model.puppet, &temp_view);
+            // Puppets are drawn back to front. The first goes straight into
+            // the frame, and the rest are layered over it.
+            scene_ctrl.update(&mut camera);
+            let mut layers: Vec<_> = current
+                .as_mut()
+                .map(|puppet| (puppet, stage.transform))
+                .into_iter()
+                .chain(guests.iter_mut().map(|x| (&mut x.puppet, x.transform)))
+                .collect();
+            layers.sort_by_key(|x| x.1.z_order);
+            let alone = layers.len() == 1;
+
+            let stale = scratch.as_ref().map_or(true, |(texture, _)| {
+                texture.size() != output.texture.size()
+                    || texture.format() != output.texture.format()
+            });
+            if layers.len() > 1 && stale {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Layer Texture"),
+                    size: output.texture.size(),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: output.texture.format(),
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[output.texture.format()],
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                scratch = Some((texture, view));
+            }
+            let scratch_view = scratch
+                .as_ref()
+                .filter(|_| layers.len() > 1)
+                .map(|(_, view)| view);
+
+            for (ind, (puppet, transform)) in layers.iter_mut().enumerate() {
+                transform.apply(&camera, puppet.camera_mut());
+                match &scratch_view {
+                    Some(scratch_view) if ind > 0 => {
+                        compositor.clear(&device, &queue, scratch_view);
+                        puppet.render(&queue, &device, scratch_view);
+                        compositor.draw(&device, &queue, scratch_view, &temp_view);
+                    }
+                    _ => puppet.render(&queue, &device, &temp_view),
+                }
+            }
+
+            if !layers.is_empty() {
                 let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
                     label: Some("Part Render Encoder"),
                 });
@@ -161,6 +444,33 @@ pub async fn run() {
                     output.texture.size(),
                 );
                 queue.submit(std::iter::once(encoder.finish()));
+
+                if screenshot {
+                    match screenshot::save(&device, &queue, &temp_texture) {
+                        Ok(path) => println!("Saved screenshot to {}", path.display()),
+                        Err(e) => eprintln!("Failed to save screenshot: {}", e),
+                    }
+                }
+            }
+            drop(layers);
+
+            // Thumbnails wait until the shown model is on stage alone, so
+            // they don't show guests.
+            if current.is_some() && alone {
+                thumbnail_in = thumbnail_in.and_then(|x| x.checked_sub(1));
+                if thumbnail_in == Some(0) {
+                    let path = state
+                        .displayed
+                        .current_name()
+                        .and_then(|name| storage::model_file(&name, THUMBNAIL_FILE).ok());
+                    if let Some(path) = path {
+                        if let Err(e) =
+                            screenshot::save_thumbnail(&device, &queue, &temp_texture, &path)
+                        {
+                            eprintln!("Failed to save thumbnail: {}", e);
+                        }
+                    }
+                }
             }
 
             let desc = wgpu::RenderPassDescriptor {
@@ -189,6 +499,70 @@ pub async fn run() {
         Event::WindowEvent { ref event, .. } => {
             let res = integration.handle_event(event);
 
+            match event {
+                WindowEvent::ModifiersChanged(state) => modifiers = *state,
+                WindowEvent::HoveredFile(path) => gui.file_hovered(path.clone()),
+                WindowEvent::HoveredFileCancelled => gui.files_left(),
+                WindowEvent::DroppedFile(path) => {
+                    let show = gui.file_dropped(path);
+                    if is_model_file(path) {
+                        state.open(path.clone(), show);
+                    } else {
+                        state
+                            .models()
+                            .report_error(path.clone(), "Not a model file".to_owned());
+                    }
+                }
+                WindowEvent::Focused(false) => {
+                    // Releases are missed while unfocused, so let go of
+                    // everything rather than leave held expressions stuck.
+                    let data = state.displayed.data();
+                    let mut expressions = data.expressions.lock().unwrap();
+                    for key in held_keys.drain() {
+                        expressions.key_released(key);
+                    }
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } => match state {
+                    // Presses egui used, like typing in a text field, aren't
+                    // hotkeys.
+                    ElementState::Pressed if held_keys.insert(*key) && !res.consumed => {
+                        if !Hotkey::is_modifier(*key) {
+                            let hotkey = Hotkey::new(*key, modifiers);
+                            if !gui.capture_hotkey(hotkey) {
+                                state.actions.key_pressed(hotkey);
+                                state
+                                    .displayed
+                                    .data()
+                                    .expressions
+                                    .lock()
+                                    .unwrap()
+                                    .key_pressed(hotkey);
+                            }
+                        }
+                    }
+                    ElementState::Pressed => {}
+                    ElementState::Released => {
+                        held_keys.remove(key);
+                        state
+                            .displayed
+                            .data()
+                            .expressions
+                            .lock()
+                            .unwrap()
+                            .key_released(*key);
+                    }
+                },
+                _ => {}
+            }
+
             if !res.consumed {
                 match event {
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
@@ -200,20 +574,17 @@ pub async fn run() {
                             surface.configure(&device, &config);
 
                             // Update the renderer's internal viewport
-                            if let Some((_, _, renderer)) = &mut current {
-                                renderer.resize(uvec2(size.width, size.height));
+                            if let Some(puppet) = &mut current {
+                                puppet.resize(uvec2(size.width, size.height));
                             }
+                            guests.resize(uvec2(size.width, size.height));
                             integration.resize(*size);
 
                             // On macos the window needs to be redrawn manually after resizing
                             window.request_redraw();
                         }
                     }
-                    _ => {
-                        if let Some((_, scene_ctrl, renderer)) = &mut current {
-                            scene_ctrl.interact(&window, event, &renderer.camera)
-                        }
-                    }
+                    _ => scene_ctrl.interact(&window, event, &camera),
                 }
             }
 
@@ -230,64 +601,63 @@ pub async fn run() {
     });
 }
 
-fn map_value(val: f32, (x1, x2): (f32, f32), (y1, y2): (f32, f32)) -> f32 {
-    (val - x1) * (y2 - y1) / (x2 - x1) + y1
-}
-
-pub fn get_value(binding: &BindingKind, data: &VmcData) -> f32 {
-    match binding {
-        BindingKind::Expr => todo!(),
-        BindingKind::Simple {
-            input,
-            input_range,
-            output_range,
-            dampen,
-        } => {
-            let initial = match input {
-                tracker::InputKind::None => 0.0f32,
-                tracker::InputKind::Blendshape(x) => data.blends.get(x).copied().unwrap(),
-                tracker::InputKind::Bone(x, v) => {
-                    let bone = data.bones.get(x).copied().unwrap();
-                    let (yaw, pitch, roll) = bone.1.to_euler(EulerRot::YXZ);
-                    match v {
-                        tracker::InputBoneKind::X => bone.0.x,
-                        tracker::InputBoneKind::Y => bone.0.y,
-                        tracker::InputBoneKind::Z => bone.0.z,
-                        tracker::InputBoneKind::Roll => roll.to_degrees(),
-                        tracker::InputBoneKind::Pitch => pitch.to_degrees(),
-                        tracker::InputBoneKind::Yaw => yaw.to_degrees(),
-                    }
-                }
-            };
-
-            map_value(initial, *input_range, *output_range)
-        }
-    }
-}
-
-pub fn apply_bindings(puppet: &mut Puppet, bindings: &ParamBindings, tracker: &TrackerSystem) {
-    puppet.begin_set_params();
+pub fn apply_bindings(
+    mixer: &mut ParamMixer,
+    bindings: &ParamBindings,
+    tracker: &TrackerSystem,
+    smoothing: &mut Smoothing,
+    dt: f32,
+) -> HashMap<String, Vec2> {
+    // Parameters whose binding was removed should stop being driven.
+    mixer.clear(LayerId::Tracking);
 
     let data = tracker.data().lock().unwrap();
-
-    for (param, binding) in bindings {
-        match binding {
+    let mut computed = HashMap::new();
+
+    // Chained parameters are evaluated after those they read, so see this
+    // frame's values. Parameters in a cycle are left undriven.
+    for param in EvaluationOrder::new(bindings).order {
+        let inputs = Inputs {
+            tracker: &data,
+            calibration: tracker.calibration(),
+            generators: tracker.generators(),
+            audio: tracker.audio(),
+            parameters: &computed,
+        };
+
+        let (target, dampen) = match &bindings[param] {
             ParamBinding::OneDim(Some(binding)) => {
-                let val = get_value(binding, &data);
-                puppet.set_param(param, Vec2::new(val, 0.0))
+                let val = binding.evaluate(&inputs);
+                (Vec2::new(val, 0.0), Vec2::new(binding.dampen(), 0.0))
             }
             ParamBinding::TwoDim(Some((binding_x, binding_y))) => {
-                let x = get_value(binding_x, &data);
-                let y = get_value(binding_y, &data);
-                puppet.set_param(param, Vec2::new(x, y))
+                let x = binding_x.evaluate(&inputs);
+                let y = binding_y.evaluate(&inputs);
+                (
+                    Vec2::new(x, y),
+                    Vec2::new(binding_x.dampen(), binding_y.dampen()),
+                )
             }
-            _ => {}
-        }
+            _ => continue,
+        };
+
+        let val = smoothing.apply(param, target, dampen, dt);
+        mixer.set(LayerId::Tracking, param, val);
+        computed.insert(param.to_owned(), val);
     }
 
-    puppet.end_set_params();
+    computed
 }
 
 fn main() {
-    pollster::block_on(run());
+    let args = Args::parse().unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        exit(2);
+    });
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    pollster::block_on(run(args));
 }
@@ -0,0 +1,327 @@
+//! Combines the parameter values written by every source (tracking,
+//! animations, manual overrides, ...) before they reach the puppet.
+
+use std::collections::{BTreeMap, HashMap};
+
+use glam::Vec2;
+use inox2d::puppet::Puppet;
+
+use crate::tracker::Axis;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LayerId {
+    Tracking,
+    Idle,
+    Animation,
+    Physics,
+    Expressions,
+    Pose,
+    Manual,
+    External,
+}
+
+impl LayerId {
+    pub const ALL: [LayerId; 8] = [
+        LayerId::Tracking,
+        LayerId::Idle,
+        LayerId::Animation,
+        LayerId::Physics,
+        LayerId::Expressions,
+        LayerId::Pose,
+        LayerId::Manual,
+        LayerId::External,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            LayerId::Tracking => "Tracking",
+            LayerId::Idle => "Idle Animation",
+            LayerId::Animation => "Animation",
+            LayerId::Physics => "Secondary Motion",
+            LayerId::Expressions => "Expressions",
+            LayerId::Pose => "Pose",
+            LayerId::Manual => "Manual Override",
+            LayerId::External => "External API",
+        }
+    }
+
+    fn default_settings(&self) -> LayerSettings {
+        let (priority, blend) = match self {
+            LayerId::Tracking => (0, BlendMode::Override),
+            LayerId::Idle => (10, BlendMode::Add),
+            LayerId::Animation => (20, BlendMode::Override),
+            LayerId::Physics => (25, BlendMode::Override),
+            LayerId::Expressions => (30, BlendMode::Override),
+            LayerId::External => (40, BlendMode::Override),
+            LayerId::Pose => (45, BlendMode::Override),
+            LayerId::Manual => (50, BlendMode::Override),
+        };
+
+        LayerSettings {
+            enabled: true,
+            priority,
+            blend,
+            weight: 1.0,
+            fade_in: 0.0,
+            fade_out: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    Override,
+    Add,
+    Multiply,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 3] = [BlendMode::Override, BlendMode::Add, BlendMode::Multiply];
+
+    pub fn name(&self) -> &str {
+        match self {
+            BlendMode::Override => "Override",
+            BlendMode::Add => "Add",
+            BlendMode::Multiply => "Multiply",
+        }
+    }
+
+    fn blend(&self, base: Vec2, value: Vec2, weight: f32) -> Vec2 {
+        match self {
+            BlendMode::Override => base.lerp(value, weight),
+            BlendMode::Add => base + value * weight,
+            BlendMode::Multiply => base * Vec2::ONE.lerp(value, weight),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LayerSettings {
+    pub enabled: bool,
+    /// Layers with a higher priority are applied later, on top of lower ones.
+    pub priority: i32,
+    pub blend: BlendMode,
+    pub weight: f32,
+    /// Seconds taken to fade the layer in after enabling it.
+    pub fade_in: f32,
+    /// Seconds taken to fade the layer out after disabling it.
+    pub fade_out: f32,
+}
+
+/// A value written to a layer, with how strongly it should apply on top of
+/// the layer's own weight.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayerValue {
+    pub value: Vec2,
+    pub weight: f32,
+}
+
+#[derive(Debug)]
+pub struct Layer {
+    pub settings: LayerSettings,
+    values: HashMap<String, LayerValue>,
+    // Parameters only written on one axis, leaving the other to lower layers.
+    axes: HashMap<String, Axis>,
+    // How far faded in the layer currently is, from 0 to 1.
+    fade: f32,
+}
+
+impl Layer {
+    fn new(id: LayerId) -> Self {
+        Self {
+            settings: id.default_settings(),
+            values: HashMap::new(),
+            axes: HashMap::new(),
+            fade: 1.0,
+        }
+    }
+
+    pub fn values(&self) -> &HashMap<String, LayerValue> {
+        &self.values
+    }
+
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    fn update_fade(&mut self, dt: f32) {
+        let (target, time) = if self.settings.enabled {
+            (1.0, self.settings.fade_in)
+        } else {
+            (0.0, self.settings.fade_out)
+        };
+
+        self.fade = if time <= 0.0 {
+            target
+        } else if self.fade < target {
+            (self.fade + dt / time).min(target)
+        } else {
+            (self.fade - dt / time).max(target)
+        };
+    }
+
+    /// Blends this layer's value for a parameter over `base`.
+    fn blend_into(&self, param: &str, base: &mut Vec2) {
+        let Some(value) = self.values.get(param) else {
+            return;
+        };
+        let weight = self.settings.weight * self.fade * value.weight;
+        let mixed = self.settings.blend.blend(*base, value.value, weight);
+        match self.axes.get(param) {
+            Some(axis) => axis.set(base, axis.get(mixed)),
+            None => *base = mixed,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParamMixer {
+    layers: BTreeMap<LayerId, Layer>,
+    output: HashMap<String, Vec2>,
+    // The puppet's default for every parameter, which layers blend over.
+    defaults: HashMap<String, Vec2>,
+}
+
+impl Default for ParamMixer {
+    fn default() -> Self {
+        Self {
+            layers: LayerId::ALL.iter().map(|x| (*x, Layer::new(*x))).collect(),
+            output: HashMap::new(),
+            defaults: HashMap::new(),
+        }
+    }
+}
+
+impl ParamMixer {
+    pub fn layer(&self, id: LayerId) -> &Layer {
+        &self.layers[&id]
+    }
+
+    pub fn layer_mut(&mut self, id: LayerId) -> &mut Layer {
+        self.layers.get_mut(&id).expect("all layers exist")
+    }
+
+    pub fn set(&mut self, id: LayerId, param: &str, value: Vec2) {
+        self.set_weighted(id, param, value, 1.0);
+    }
+
+    /// Sets a value that only partially applies, such as one fading in.
+    pub fn set_weighted(&mut self, id: LayerId, param: &str, value: Vec2, weight: f32) {
+        let value = LayerValue { value, weight };
+        let layer = self.layer_mut(id);
+        layer.axes.remove(param);
+        if let Some(old) = layer.values.get_mut(param) {
+            *old = value;
+        } else {
+            layer.values.insert(param.to_owned(), value);
+        }
+    }
+
+    /// Sets one axis of a 2D parameter, leaving the other as lower layers
+    /// have it.
+    pub fn set_axis(&mut self, id: LayerId, param: &str, axis: Axis, value: f32) {
+        let layer = self.layer_mut(id);
+        if let Some(old) = layer.values.get_mut(param) {
+            axis.set(&mut old.value, value);
+            // Writing the other axis as well leaves both written.
+            if layer.axes.get(param).is_some_and(|x| *x != axis) {
+                layer.axes.remove(param);
+            }
+        } else {
+            let mut written = Vec2::ZERO;
+            axis.set(&mut written, value);
+            layer.values.insert(
+                param.to_owned(),
+                LayerValue {
+                    value: written,
+                    weight: 1.0,
+                },
+            );
+            layer.axes.insert(param.to_owned(), axis);
+        }
+    }
+
+    pub fn get(&self, id: LayerId, param: &str) -> Option<Vec2> {
+        self.layer(id).values.get(param).map(|x| x.value)
+    }
+
+    pub fn remove(&mut self, id: LayerId, param: &str) {
+        let layer = self.layer_mut(id);
+        layer.values.remove(param);
+        layer.axes.remove(param);
+    }
+
+    /// Removes every value written to the layer.
+    pub fn clear(&mut self, id: LayerId) {
+        let layer = self.layer_mut(id);
+        layer.values.clear();
+        layer.axes.clear();
+    }
+
+    /// Removes every written value, for when the shown model changes.
+    pub fn reset(&mut self) {
+        for layer in self.layers.values_mut() {
+            layer.values.clear();
+            layer.axes.clear();
+        }
+        self.output.clear();
+        self.defaults.clear();
+    }
+
+    /// A parameter as mixed from this frame's values of the layers up to and
+    /// including `id`, for sources that shouldn't read their own last output
+    /// back. `None` until the puppet's defaults are known.
+    pub fn mixed_up_to(&self, id: LayerId, param: &str) -> Option<Vec2> {
+        let priority = self.layer(id).settings.priority;
+        let mut order: Vec<_> = self
+            .layers
+            .values()
+            .filter(|x| x.settings.priority <= priority)
+            .collect();
+        order.sort_by_key(|x| x.settings.priority);
+
+        let mut out = *self.defaults.get(param)?;
+        for layer in order {
+            layer.blend_into(param, &mut out);
+        }
+        Some(out)
+    }
+
+    /// The values last sent to the puppet.
+    pub fn output(&self) -> &HashMap<String, Vec2> {
+        &self.output
+    }
+
+    /// Mixes every layer and sets the resulting parameters on the puppet.
+    pub fn apply(&mut self, puppet: &mut Puppet, dt: f32) {
+        for layer in self.layers.values_mut() {
+            layer.update_fade(dt);
+        }
+
+        let mut order: Vec<_> = self.layers.values().collect();
+        order.sort_by_key(|x| x.settings.priority);
+
+        self.output.clear();
+        for layer in order {
+            if layer.settings.weight * layer.fade <= 0.0 {
+                continue;
+            }
+
+            for param in layer.values.keys() {
+                let Some(info) = puppet.parameters.get(param) else {
+                    continue;
+                };
+
+                self.defaults.insert(param.clone(), info.defaults);
+                let base = self.output.entry(param.clone()).or_insert(info.defaults);
+                layer.blend_into(param, base);
+            }
+        }
+
+        puppet.begin_set_params();
+        for (param, value) in &self.output {
+            puppet.set_param(param, *value);
+        }
+        puppet.end_set_params();
+    }
+}
@@ -0,0 +1,147 @@
+//! Outfits, named sets of parts shown or hidden together, like glasses, a
+//! hat or alternate clothing.
+
+use std::collections::{BTreeSet, HashMap};
+
+use inox2d::nodes::node::InoxNodeUuid;
+use serde::{Deserialize, Serialize};
+
+use crate::{animation::step_towards, app::model::Stored};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Outfit {
+    pub name: String,
+    /// Nodes only drawn while the outfit is on, by UUID. Everything under
+    /// them goes with them.
+    pub show: BTreeSet<u32>,
+    /// Nodes not drawn while the outfit is on.
+    pub hide: BTreeSet<u32>,
+    /// Turning on an outfit turns off every other one in its group.
+    pub group: String,
+    /// Seconds taken to cross-fade parts in and out.
+    pub fade: f32,
+}
+
+impl Outfit {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            show: BTreeSet::new(),
+            hide: BTreeSet::new(),
+            group: String::new(),
+            fade: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    active: bool,
+    weight: f32,
+}
+
+/// The outfits stored for the displayed model, and how far each one is
+/// faded in.
+#[derive(Debug, Default)]
+pub struct OutfitPlayer {
+    outfits: Vec<Outfit>,
+    states: HashMap<String, State>,
+}
+
+impl Stored for OutfitPlayer {
+    const FILE: &'static str = "outfits.json";
+    const NAME: &'static str = "outfits";
+    type Data = Vec<Outfit>;
+
+    fn from_stored(outfits: Vec<Outfit>) -> Self {
+        Self {
+            outfits,
+            ..Self::default()
+        }
+    }
+
+    fn stored(&self) -> &Vec<Outfit> {
+        &self.outfits
+    }
+}
+
+impl OutfitPlayer {
+    pub fn outfits(&self) -> &[Outfit] {
+        &self.outfits
+    }
+
+    pub fn outfits_mut(&mut self) -> &mut Vec<Outfit> {
+        &mut self.outfits
+    }
+
+    pub fn outfit(&self, name: &str) -> Option<&Outfit> {
+        self.outfits.iter().find(|x| x.name == name)
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        self.states.get(name).is_some_and(|x| x.active)
+    }
+
+    /// How far an outfit is faded in, from 0 to 1.
+    pub fn weight(&self, name: &str) -> f32 {
+        self.states.get(name).map_or(0.0, |x| x.weight)
+    }
+
+    pub fn set_active(&mut self, name: &str, active: bool) {
+        let Some(outfit) = self.outfit(name) else {
+            return;
+        };
+
+        if active && !outfit.group.is_empty() {
+            let group = outfit.group.clone();
+            for other in &self.outfits {
+                if other.group == group && other.name != name {
+                    if let Some(state) = self.states.get_mut(&other.name) {
+                        state.active = false;
+                    }
+                }
+            }
+        }
+
+        self.states.entry(name.to_owned()).or_default().active = active;
+    }
+
+    pub fn toggle(&mut self, name: &str) {
+        self.set_active(name, !self.is_active(name));
+    }
+
+    /// Advances fades.
+    pub fn update(&mut self, dt: f32) {
+        let states = &mut self.states;
+        states.retain(|name, _| self.outfits.iter().any(|x| &x.name == name));
+
+        for outfit in &self.outfits {
+            let Some(state) = states.get_mut(&outfit.name) else {
+                continue;
+            };
+            let target = if state.active { 1.0 } else { 0.0 };
+            state.weight = step_towards(state.weight, target, dt, outfit.fade);
+        }
+    }
+
+    /// How visible the outfits leave each node they name, from 0 to 1. A node
+    /// one outfit shows is as visible as the most faded in outfit showing
+    /// it, less however much those hiding it are faded in.
+    pub fn visibility(&self) -> HashMap<InoxNodeUuid, f32> {
+        let mut out = HashMap::new();
+        for outfit in &self.outfits {
+            let weight = self.weight(&outfit.name);
+            for uuid in &outfit.show {
+                let entry = out.entry(InoxNodeUuid(*uuid)).or_insert(0.0f32);
+                *entry = entry.max(weight);
+            }
+        }
+        for outfit in &self.outfits {
+            let weight = self.weight(&outfit.name);
+            for uuid in &outfit.hide {
+                *out.entry(InoxNodeUuid(*uuid)).or_insert(1.0) *= 1.0 - weight;
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,126 @@
+//! Which parts of the displayed puppet are drawn, applied over the opacities
+//! the puppet gives them. Outfits fade parts in and out through here too.
+
+use std::collections::{HashMap, HashSet};
+
+use inox2d::{
+    nodes::{node::InoxNodeUuid, node_data::InoxData},
+    puppet::Puppet,
+};
+
+/// How visible parts outside the highlighted node are, so it stands out.
+const DIMMED: f32 = 0.2;
+
+/// The opacity of a node that has one, which is parts and composites.
+pub fn opacity_mut(data: &mut InoxData) -> Option<&mut f32> {
+    match data {
+        InoxData::Part(part) => Some(&mut part.draw_state.blending.opacity),
+        InoxData::Composite(composite) => Some(&mut composite.draw_state.blending.opacity),
+        _ => None,
+    }
+}
+
+fn is_composite(data: &InoxData) -> bool {
+    matches!(data, InoxData::Composite(_))
+}
+
+#[derive(Debug, Default)]
+pub struct PartVisibility {
+    /// Nodes hidden along with everything under them.
+    hidden: HashSet<InoxNodeUuid>,
+    /// A node shown over everything else, which is dimmed.
+    highlighted: Option<InoxNodeUuid>,
+    // The puppet's own opacities for nodes that have been changed, to go
+    // back to once they no longer are.
+    original: HashMap<InoxNodeUuid, f32>,
+}
+
+impl PartVisibility {
+    /// Forgets everything for the previous puppet.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_hidden(&self, uuid: InoxNodeUuid) -> bool {
+        self.hidden.contains(&uuid)
+    }
+
+    pub fn set_hidden(&mut self, uuid: InoxNodeUuid, hidden: bool) {
+        if hidden {
+            self.hidden.insert(uuid);
+        } else {
+            self.hidden.remove(&uuid);
+        }
+    }
+
+    pub fn show_all(&mut self) {
+        self.hidden.clear();
+    }
+
+    pub fn highlighted(&self) -> Option<InoxNodeUuid> {
+        self.highlighted
+    }
+
+    pub fn set_highlighted(&mut self, uuid: Option<InoxNodeUuid>) {
+        self.highlighted = uuid;
+    }
+
+    /// Sets each node's opacity from whether it's hidden or highlighted, and
+    /// how visible outfits leave it.
+    pub fn apply(&mut self, puppet: &mut Puppet, outfits: &HashMap<InoxNodeUuid, f32>) {
+        let nodes = &mut puppet.nodes;
+        let root = nodes.root;
+        let ids: Vec<_> = root.descendants(&nodes.arena).collect();
+
+        // Composites draw what's under them with their own opacity, so one
+        // above the highlighted node is left lit rather than dimming it too.
+        let lit = self.highlighted.and_then(|uuid| {
+            let id = ids.iter().find(|x| nodes.arena[**x].get().uuid == uuid)?;
+            let mut lit: HashSet<_> = id.descendants(&nodes.arena).collect();
+            lit.extend(
+                id.ancestors(&nodes.arena)
+                    .filter(|x| is_composite(&nodes.arena[*x].get().data)),
+            );
+            Some(lit)
+        });
+        let dimmed = |id| lit.as_ref().is_some_and(|lit| !lit.contains(&id));
+
+        for id in ids {
+            // Only what's below the nearest composite is applied here, as the
+            // composite's opacity already carries the rest.
+            let mut hidden = false;
+            let mut outfit = 1.0;
+            let mut composite = None;
+            for ancestor in id.ancestors(&nodes.arena) {
+                let node = nodes.arena[ancestor].get();
+                if ancestor != id && is_composite(&node.data) {
+                    composite = Some(ancestor);
+                    break;
+                }
+                hidden |= self.hidden.contains(&node.uuid);
+                outfit *= outfits.get(&node.uuid).copied().unwrap_or(1.0);
+            }
+            let factor = if hidden {
+                0.0
+            } else if dimmed(id) && !composite.is_some_and(dimmed) {
+                DIMMED * outfit
+            } else {
+                outfit
+            };
+
+            let node = nodes.arena[id].get_mut();
+            let uuid = node.uuid;
+            let Some(opacity) = opacity_mut(&mut node.data) else {
+                continue;
+            };
+            if factor == 1.0 {
+                if let Some(original) = self.original.remove(&uuid) {
+                    *opacity = original;
+                }
+            } else {
+                let original = *self.original.entry(uuid).or_insert(*opacity);
+                *opacity = original * factor;
+            }
+        }
+    }
+}
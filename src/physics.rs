@@ -0,0 +1,213 @@
+//! Secondary motion, where parameters such as hair sway are driven by damped
+//! springs following other parameters or tracker inputs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::model::Stored,
+    mixer::{LayerId, ParamMixer},
+    tracker::{map_value, Axis, InputKind, Inputs},
+};
+
+/// Springs are simulated in steps of this many seconds, so they behave the
+/// same whatever the frame rate.
+const TIME_STEP: f32 = 1.0 / 120.0;
+/// The most steps simulated in one frame, so a long stall doesn't freeze
+/// the app trying to catch up.
+const MAX_STEPS: u32 = 30;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpringSource {
+    /// A parameter's value as of the previous frame.
+    Parameter(String, Axis),
+    Input(InputKind),
+}
+
+impl SpringSource {
+    pub fn name(&self) -> String {
+        match self {
+            SpringSource::Parameter(name, axis) => format!("{} ({})", name, axis.name()),
+            SpringSource::Input(input) => input.name(),
+        }
+    }
+}
+
+/// What of the source the spring is pulled towards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceMode {
+    Value,
+    /// How fast the source is changing, per second, so the spring swings
+    /// while the source moves and settles once it stops.
+    Velocity,
+}
+
+impl SourceMode {
+    pub const ALL: [SourceMode; 2] = [SourceMode::Value, SourceMode::Velocity];
+
+    pub fn name(&self) -> &str {
+        match self {
+            SourceMode::Value => "Value",
+            SourceMode::Velocity => "Velocity",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Spring {
+    pub enabled: bool,
+    pub param: String,
+    pub axis: Axis,
+    pub source: SpringSource,
+    pub mode: SourceMode,
+    pub input_range: (f32, f32),
+    pub output_range: (f32, f32),
+    /// How strongly the spring pulls towards its target.
+    pub stiffness: f32,
+    /// How quickly swinging dies down.
+    pub damping: f32,
+    /// A constant pull, in output units per second squared.
+    pub gravity: f32,
+}
+
+impl Spring {
+    pub fn new(param: String, axis: Axis) -> Self {
+        Self {
+            enabled: true,
+            param,
+            axis,
+            source: SpringSource::Input(InputKind::None),
+            mode: SourceMode::Velocity,
+            input_range: (-90.0, 90.0),
+            output_range: (1.0, -1.0),
+            stiffness: 80.0,
+            damping: 6.0,
+            gravity: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpringState {
+    pub position: f32,
+    velocity: f32,
+    previous_source: Option<f32>,
+    initialized: bool,
+}
+
+/// The springs stored for the displayed model, and their simulation.
+#[derive(Debug, Default)]
+pub struct Physics {
+    springs: Vec<Spring>,
+    states: Vec<SpringState>,
+    accumulator: f32,
+}
+
+impl Stored for Physics {
+    const FILE: &'static str = "physics.json";
+    const NAME: &'static str = "physics";
+    type Data = Vec<Spring>;
+
+    fn from_stored(springs: Vec<Spring>) -> Self {
+        Self {
+            springs,
+            ..Self::default()
+        }
+    }
+
+    fn stored(&self) -> &Vec<Spring> {
+        &self.springs
+    }
+}
+
+impl Physics {
+    pub fn springs(&self) -> &[Spring] {
+        &self.springs
+    }
+
+    pub fn springs_mut(&mut self) -> &mut Vec<Spring> {
+        &mut self.springs
+    }
+
+    pub fn remove(&mut self, ind: usize) -> Spring {
+        if ind < self.states.len() {
+            self.states.remove(ind);
+        }
+        self.springs.remove(ind)
+    }
+
+    pub fn state(&self, ind: usize) -> Option<&SpringState> {
+        self.states.get(ind)
+    }
+
+    /// Lets every spring come to rest where its target currently is.
+    pub fn settle(&mut self) {
+        self.states.clear();
+    }
+
+    /// Steps the simulation and writes the spring driven parameters to the
+    /// mixer.
+    pub fn update(&mut self, dt: f32, inputs: &Inputs, mixer: &mut ParamMixer) {
+        mixer.clear(LayerId::Physics);
+
+        // Springs may have been added or removed since the last update.
+        self.states
+            .resize_with(self.springs.len(), Default::default);
+
+        self.accumulator += dt;
+        let steps = ((self.accumulator / TIME_STEP) as u32).min(MAX_STEPS);
+        self.accumulator = (self.accumulator - steps as f32 * TIME_STEP).min(TIME_STEP);
+
+        for (spring, state) in self.springs.iter().zip(&mut self.states) {
+            if !spring.enabled {
+                continue;
+            }
+
+            let source = match &spring.source {
+                // Read from this frame's layers, so a spring following its own
+                // parameter doesn't chase its last output.
+                SpringSource::Parameter(name, axis) => mixer
+                    .mixed_up_to(LayerId::Physics, name)
+                    .map(|x| axis.get(x)),
+                SpringSource::Input(input) => input.value(inputs),
+            };
+
+            let raw = match spring.mode {
+                SourceMode::Value => source.unwrap_or(0.0),
+                SourceMode::Velocity => {
+                    let velocity = match (source, state.previous_source) {
+                        (Some(now), Some(before)) if dt > 0.0 => (now - before) / dt,
+                        _ => 0.0,
+                    };
+                    state.previous_source = source;
+                    velocity
+                }
+            };
+            let target = map_value(raw, spring.input_range, spring.output_range);
+
+            if !state.initialized {
+                state.position = target;
+                state.initialized = true;
+            }
+
+            for _ in 0..steps {
+                let acceleration = spring.stiffness * (target - state.position)
+                    - spring.damping * state.velocity
+                    + spring.gravity;
+                // Semi-implicit Euler, which stays stable for stiff springs.
+                state.velocity += acceleration * TIME_STEP;
+                state.position += state.velocity * TIME_STEP;
+            }
+
+            let (low, high) = (
+                spring.output_range.0.min(spring.output_range.1),
+                spring.output_range.0.max(spring.output_range.1),
+            );
+            if state.position < low || state.position > high {
+                state.position = state.position.clamp(low, high);
+                state.velocity = 0.0;
+            }
+
+            mixer.set_axis(LayerId::Physics, &spring.param, spring.axis, state.position);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Named snapshots of every parameter of a puppet, which can be recalled
+//! over everything else, such as for a BRB screen.
+
+use std::collections::{BTreeMap, HashMap};
+
+use glam::Vec2;
+use inox2d::puppet::Puppet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::model::Stored,
+    mixer::{LayerId, LayerValue, ParamMixer},
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pose {
+    pub name: String,
+    pub values: BTreeMap<String, Vec2>,
+}
+
+impl Pose {
+    /// Captures the values last sent to the puppet, with parameters nothing
+    /// wrote to at their defaults.
+    pub fn capture(name: String, puppet: &Puppet, mixer: &ParamMixer) -> Self {
+        let values = puppet
+            .parameters
+            .iter()
+            .map(|(param, info)| {
+                let value = mixer.output().get(param).copied();
+                (param.clone(), value.unwrap_or(info.defaults))
+            })
+            .collect();
+
+        Self { name, values }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Blend {
+    from: HashMap<String, LayerValue>,
+    to: BTreeMap<String, Vec2>,
+    /// Seconds the blend takes.
+    time: f32,
+    progress: f32,
+}
+
+/// The poses stored for the displayed model, and which one is applied.
+#[derive(Debug, Default)]
+pub struct PosePlayer {
+    poses: Vec<Pose>,
+    applied: Option<String>,
+    blend: Blend,
+    // What was written to the mixer last, to blend onwards from.
+    current: HashMap<String, LayerValue>,
+}
+
+impl Stored for PosePlayer {
+    const FILE: &'static str = "poses.json";
+    const NAME: &'static str = "poses";
+    type Data = Vec<Pose>;
+
+    fn from_stored(poses: Vec<Pose>) -> Self {
+        Self {
+            poses,
+            ..Self::default()
+        }
+    }
+
+    fn stored(&self) -> &Vec<Pose> {
+        &self.poses
+    }
+}
+
+impl PosePlayer {
+    pub fn poses(&self) -> &[Pose] {
+        &self.poses
+    }
+
+    pub fn poses_mut(&mut self) -> &mut Vec<Pose> {
+        &mut self.poses
+    }
+
+    pub fn pose(&self, name: &str) -> Option<&Pose> {
+        self.poses.iter().find(|x| x.name == name)
+    }
+
+    /// The name of the applied pose, if any.
+    pub fn applied(&self) -> Option<&str> {
+        self.applied.as_deref()
+    }
+
+    /// Blends from wherever the puppet is to a pose over `time` seconds,
+    /// where no time applies it instantly.
+    pub fn apply(&mut self, name: &str, time: f32) {
+        let Some(pose) = self.pose(name) else {
+            return;
+        };
+
+        let to = pose.values.clone();
+        self.start_blend(to, time);
+        self.applied = Some(name.to_owned());
+    }
+
+    /// Blends from the applied pose back to the other sources over `time`
+    /// seconds.
+    pub fn release(&mut self, time: f32) {
+        self.start_blend(BTreeMap::new(), time);
+        self.applied = None;
+    }
+
+    fn start_blend(&mut self, to: BTreeMap<String, Vec2>, time: f32) {
+        self.blend = Blend {
+            from: self.current.clone(),
+            to,
+            time,
+            progress: 0.0,
+        };
+    }
+
+    /// Advances the blend and writes the pose to the mixer.
+    pub fn update(&mut self, dt: f32, mixer: &mut ParamMixer) {
+        mixer.clear(LayerId::Pose);
+
+        let blend = &mut self.blend;
+        blend.progress = if blend.time <= 0.0 {
+            1.0
+        } else {
+            (blend.progress + dt / blend.time).min(1.0)
+        };
+        let t = blend.progress;
+
+        self.current.clear();
+        for (param, value) in &blend.to {
+            let from = blend.from.get(param).copied().unwrap_or(LayerValue {
+                value: *value,
+                weight: 0.0,
+            });
+            self.current.insert(
+                param.clone(),
+                LayerValue {
+                    value: from.value.lerp(*value, t),
+                    weight: from.weight + (1.0 - from.weight) * t,
+                },
+            );
+        }
+        // Parameters only in the previous pose fade out where they were.
+        for (param, from) in &blend.from {
+            if !blend.to.contains_key(param) && t < 1.0 {
+                self.current.insert(
+                    param.clone(),
+                    LayerValue {
+                        value: from.value,
+                        weight: from.weight * (1.0 - t),
+                    },
+                );
+            }
+        }
+
+        for (param, value) in &self.current {
+            mixer.set_weighted(LayerId::Pose, param, value.value, value.weight);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Saving the rendered puppet, without the UI, as a PNG, either as a
+//! screenshot or as a model's thumbnail.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui_wgpu::wgpu;
+use thiserror::Error;
+
+use crate::app::storage::{self, StorageError};
+
+#[derive(Error, Debug)]
+pub enum ScreenshotError {
+    #[error("could not find where to save screenshots")]
+    Storage(#[from] StorageError),
+    #[error("could not read the rendered image")]
+    Readback(#[from] wgpu::BufferAsyncError),
+    #[error("could not write the screenshot")]
+    Io(#[from] io::Error),
+    #[error("could not encode the screenshot")]
+    Encoding(#[from] png::EncodingError),
+}
+
+/// The longest side of a model thumbnail, in pixels.
+const THUMBNAIL_SIZE: u32 = 128;
+
+/// An RGBA image read back from the GPU.
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Copies a BGRA texture back from the GPU and saves it, returning where.
+pub fn save(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<PathBuf, ScreenshotError> {
+    let image = read_back(device, queue, texture)?;
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    let path = storage::app_file(&format!("screenshots/virst-{}.png", seconds))?;
+    write_png(&path, &image)?;
+
+    Ok(path)
+}
+
+/// Saves a small copy of a BGRA texture, for showing in the model library.
+pub fn save_thumbnail(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    path: &Path,
+) -> Result<(), ScreenshotError> {
+    let image = read_back(device, queue, texture)?;
+
+    let scale = (image.width.max(image.height) as f32 / THUMBNAIL_SIZE as f32).max(1.0);
+    let width = ((image.width as f32 / scale) as u32).max(1);
+    let height = ((image.height as f32 / scale) as u32).max(1);
+
+    // Each thumbnail pixel averages the block of pixels it covers.
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        let (y0, y1) = (y * image.height / height, (y + 1) * image.height / height);
+        for x in 0..width {
+            let (x0, x1) = (x * image.width / width, (x + 1) * image.width / width);
+            let mut sum = [0u32; 4];
+            for sy in y0..y1.max(y0 + 1) {
+                for sx in x0..x1.max(x0 + 1) {
+                    let at = ((sy * image.width + sx) * 4) as usize;
+                    for (total, channel) in sum.iter_mut().zip(&image.pixels[at..at + 4]) {
+                        *total += *channel as u32;
+                    }
+                }
+            }
+            let count = (y1.max(y0 + 1) - y0) * (x1.max(x0 + 1) - x0);
+            pixels.extend(sum.map(|x| (x / count) as u8));
+        }
+    }
+
+    write_png(
+        path,
+        &Image {
+            width,
+            height,
+            pixels,
+        },
+    )
+}
+
+fn read_back(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<Image, ScreenshotError> {
+    let size = texture.size();
+    // Rows of a buffer copy must be aligned, so may have padding at the end.
+    let row_bytes = size.width * 4;
+    let padded_row_bytes =
+        row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Screenshot Buffer"),
+        size: (padded_row_bytes * size.height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Screenshot Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, move |res| {
+        let _ = sender.send(res);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver.recv().expect("map callback runs after waiting")?;
+
+    let mut pixels = Vec::with_capacity((row_bytes * size.height) as usize);
+    for row in slice.get_mapped_range().chunks(padded_row_bytes as usize) {
+        for pixel in row[..row_bytes as usize].chunks(4) {
+            // The renderer outputs premultiplied alpha, while PNG doesn't.
+            let alpha = pixel[3];
+            let unpremultiply = |x: u8| {
+                if alpha == 0 {
+                    0
+                } else {
+                    (x as u32 * 255 / alpha as u32).min(255) as u8
+                }
+            };
+            pixels.extend([
+                unpremultiply(pixel[2]),
+                unpremultiply(pixel[1]),
+                unpremultiply(pixel[0]),
+                alpha,
+            ]);
+        }
+    }
+    buffer.unmap();
+
+    Ok(Image {
+        width: size.width,
+        height: size.height,
+        pixels,
+    })
+}
+
+fn write_png(path: &Path, image: &Image) -> Result<(), ScreenshotError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        image.width,
+        image.height,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&image.pixels)?;
+
+    Ok(())
+}
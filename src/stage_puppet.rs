@@ -0,0 +1,117 @@
+//! A puppet of either kind, set up to be rendered on stage.
+
+use egui_wgpu::wgpu;
+use glam::UVec2;
+use inox2d::{math::camera::Camera, model::Model};
+use inox2d_wgpu::Renderer;
+
+use crate::{
+    app::model::PuppetKind,
+    static_puppet::{StaticMotion, StaticOverride, StaticPose, StaticPuppet, StaticRenderer},
+    tracker::Inputs,
+};
+
+pub enum StagePuppet {
+    Inochi2D(Model, Renderer),
+    Static {
+        puppet: StaticPuppet,
+        motion: StaticMotion,
+        pose: StaticPose,
+        renderer: StaticRenderer,
+    },
+}
+
+impl StagePuppet {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        kind: &PuppetKind,
+        viewport: UVec2,
+    ) -> Self {
+        match kind {
+            PuppetKind::Inochi2D(model) => Self::from_model(device, queue, model.clone(), viewport),
+            PuppetKind::Static(puppet) => {
+                Self::from_static(device, queue, puppet.clone(), viewport)
+            }
+        }
+    }
+
+    pub fn from_model(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model: Model,
+        viewport: UVec2,
+    ) -> Self {
+        let renderer = Renderer::new(
+            device,
+            queue,
+            wgpu::TextureFormat::Bgra8Unorm,
+            &model,
+            viewport,
+        );
+        StagePuppet::Inochi2D(model, renderer)
+    }
+
+    pub fn from_static(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        puppet: StaticPuppet,
+        viewport: UVec2,
+    ) -> Self {
+        let renderer = StaticRenderer::new(
+            device,
+            queue,
+            wgpu::TextureFormat::Bgra8Unorm,
+            &puppet,
+            viewport,
+        );
+        StagePuppet::Static {
+            puppet,
+            motion: StaticMotion::default(),
+            pose: StaticPose::default(),
+            renderer,
+        }
+    }
+
+    /// Moves a static puppet to follow its inputs, with any settings changed
+    /// for it. Inochi2D puppets are moved through their parameters instead.
+    pub fn update_static(&mut self, dt: f32, inputs: &Inputs, overrides: &StaticOverride) {
+        if let StagePuppet::Static {
+            puppet,
+            motion,
+            pose,
+            ..
+        } = self
+        {
+            *pose = motion.update(dt, overrides.settings(puppet), inputs);
+        }
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        match self {
+            StagePuppet::Inochi2D(_, renderer) => &mut renderer.camera,
+            StagePuppet::Static { renderer, .. } => &mut renderer.camera,
+        }
+    }
+
+    pub fn resize(&mut self, viewport: UVec2) {
+        match self {
+            StagePuppet::Inochi2D(_, renderer) => renderer.resize(viewport),
+            StagePuppet::Static { renderer, .. } => renderer.resize(viewport),
+        }
+    }
+
+    pub fn render(&self, queue: &wgpu::Queue, device: &wgpu::Device, target: &wgpu::TextureView) {
+        match self {
+            StagePuppet::Inochi2D(model, renderer) => {
+                renderer.render(queue, device, &model.puppet, target)
+            }
+            StagePuppet::Static {
+                puppet,
+                pose,
+                renderer,
+                ..
+            } => renderer.render(queue, device, puppet, pose, target),
+        }
+    }
+}
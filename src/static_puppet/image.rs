@@ -0,0 +1,92 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    path::Path,
+};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ImageError {
+    #[error("could not read the image")]
+    Io(#[from] io::Error),
+    #[error("could not decode the PNG")]
+    Png(#[from] png::DecodingError),
+    #[error("could not decode the JPEG")]
+    Jpeg(#[from] jpeg_decoder::Error),
+    #[error("unsupported image format")]
+    Unsupported,
+}
+
+/// An image decoded to 8 bit RGBA, without premultiplied alpha.
+#[derive(Debug)]
+pub struct StaticImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl StaticImage {
+    /// Opens a PNG or JPEG, going by its extension.
+    pub fn open(path: &Path) -> Result<StaticImage, ImageError> {
+        let ext = path
+            .extension()
+            .map(|x| x.to_string_lossy().to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("png") => Self::open_png(path),
+            Some("jpg" | "jpeg") => Self::open_jpeg(path),
+            _ => Err(ImageError::Unsupported),
+        }
+    }
+
+    fn open_png(path: &Path) -> Result<StaticImage, ImageError> {
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        // Palettes and 16 bit channels come out as 8 bit RGB or grayscale.
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let data = &buf[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => data.to_vec(),
+            png::ColorType::Rgb => data
+                .chunks(3)
+                .flat_map(|x| [x[0], x[1], x[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks(2)
+                .flat_map(|x| [x[0], x[0], x[0], x[1]])
+                .collect(),
+            png::ColorType::Grayscale => data.iter().flat_map(|x| [*x, *x, *x, 255]).collect(),
+            png::ColorType::Indexed => return Err(ImageError::Unsupported),
+        };
+
+        Ok(StaticImage {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    fn open_jpeg(path: &Path) -> Result<StaticImage, ImageError> {
+        let mut decoder = jpeg_decoder::Decoder::new(fs::read(path)?.as_slice());
+        let data = decoder.decode()?;
+        let info = decoder.info().ok_or(ImageError::Unsupported)?;
+
+        let pixels = match info.pixel_format {
+            jpeg_decoder::PixelFormat::RGB24 => data
+                .chunks(3)
+                .flat_map(|x| [x[0], x[1], x[2], 255])
+                .collect(),
+            jpeg_decoder::PixelFormat::L8 => data.iter().flat_map(|x| [*x, *x, *x, 255]).collect(),
+            _ => return Err(ImageError::Unsupported),
+        };
+
+        Ok(StaticImage {
+            width: info.width as u32,
+            height: info.height as u32,
+            pixels,
+        })
+    }
+}
@@ -0,0 +1,258 @@
+//! Puppets made of still images, swapped between as the puppet talks and
+//! blinks, PNGTuber style.
+//!
+//! A static puppet is either a single PNG or JPEG image, or a JSON file
+//! listing image layers and when each is shown:
+//!
+//! ```json
+//! {
+//!     "name": "Example",
+//!     "layers": [
+//!         { "image": "body.png" },
+//!         { "image": "mouth_closed.png", "talking": false },
+//!         { "image": "mouth_open.png", "talking": true },
+//!         { "image": "eyes_closed.png", "blinking": true }
+//!     ],
+//!     "settings": { "bounce": 30.0 }
+//! }
+//! ```
+
+mod image;
+mod motion;
+mod render;
+
+pub use image::{ImageError, StaticImage};
+pub use motion::{StaticMotion, StaticPose};
+pub use render::StaticRenderer;
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{app::model::Stored, audio::AudioKind, tracker::InputKind};
+
+/// File extensions a static puppet can be loaded from.
+pub const EXTENSIONS: [&str; 4] = ["json", "png", "jpg", "jpeg"];
+
+/// The highest `talk_threshold` goes, leaving room above it for shaking to
+/// build up in.
+pub const MAX_TALK_THRESHOLD: f32 = 0.95;
+
+#[derive(Error, Debug)]
+pub enum StaticError {
+    #[error("could not read file")]
+    Io(#[from] io::Error),
+    #[error("invalid static puppet file")]
+    Manifest(#[from] serde_json::Error),
+    #[error("could not load layer {}", .0.display())]
+    Layer(PathBuf, #[source] ImageError),
+    #[error("the puppet has no layers")]
+    NoLayers,
+}
+
+impl StaticError {
+    /// Whether the error came from a file that doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            StaticError::Io(e) => e.kind() == io::ErrorKind::NotFound,
+            StaticError::Layer(_, ImageError::Io(e)) => e.kind() == io::ErrorKind::NotFound,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaticSettings {
+    /// The puppet talks while this input is above `talk_threshold`. It also
+    /// drives bouncing and shaking.
+    pub talk_input: InputKind,
+    pub talk_threshold: f32,
+    /// The puppet blinks while this input is above `blink_threshold`, or
+    /// every `blink_interval` seconds if there's none.
+    pub blink_input: Option<InputKind>,
+    pub blink_threshold: f32,
+    pub blink_interval: f32,
+    pub blink_duration: f32,
+    /// How high the puppet hops while talking, in image pixels.
+    pub bounce: f32,
+    /// Hops per second.
+    pub bounce_speed: f32,
+    /// How far the puppet shakes at full volume, in image pixels.
+    pub shake: f32,
+    /// Model units per image pixel, so images come out around the size of an
+    /// Inochi2D puppet.
+    pub scale: f32,
+}
+
+impl Default for StaticSettings {
+    fn default() -> Self {
+        Self {
+            talk_input: InputKind::Audio(AudioKind::Volume),
+            talk_threshold: 0.2,
+            blink_input: None,
+            blink_threshold: 0.5,
+            blink_interval: 4.0,
+            blink_duration: 0.15,
+            bounce: 20.0,
+            bounce_speed: 4.0,
+            shake: 0.0,
+            scale: 8.0,
+        }
+    }
+}
+
+/// Settings changed in the app, used instead of those from the puppet's own
+/// file.
+#[derive(Debug, Default)]
+pub struct StaticOverride {
+    settings: Option<StaticSettings>,
+}
+
+impl Stored for StaticOverride {
+    const FILE: &'static str = "static.json";
+    const NAME: &'static str = "static puppet settings";
+    type Data = Option<StaticSettings>;
+
+    fn from_stored(settings: Option<StaticSettings>) -> Self {
+        Self { settings }
+    }
+
+    fn stored(&self) -> &Option<StaticSettings> {
+        &self.settings
+    }
+}
+
+impl StaticOverride {
+    /// The settings in use, which are the puppet's own unless changed.
+    pub fn settings<'a>(&'a self, puppet: &'a StaticPuppet) -> &'a StaticSettings {
+        self.settings.as_ref().unwrap_or(&puppet.settings)
+    }
+
+    /// The settings to change, starting from the puppet's own.
+    pub fn settings_mut(&mut self, puppet: &StaticPuppet) -> &mut StaticSettings {
+        self.settings.get_or_insert_with(|| puppet.settings.clone())
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.settings.is_some()
+    }
+
+    /// Goes back to the puppet's own settings.
+    pub fn reset(&mut self) {
+        self.settings = None;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StaticLayer {
+    pub image: Arc<StaticImage>,
+    /// Where the middle of the image sits, in image pixels from the middle
+    /// of the puppet.
+    pub offset: Vec2,
+    /// Shows the layer only while talking, or only while not. Shown either
+    /// way if unset.
+    pub talking: Option<bool>,
+    /// Shows the layer only while blinking, or only while not.
+    pub blinking: Option<bool>,
+}
+
+impl StaticLayer {
+    pub fn visible(&self, pose: &StaticPose) -> bool {
+        self.talking.map_or(true, |x| x == pose.talking)
+            && self.blinking.map_or(true, |x| x == pose.blinking)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StaticPuppet {
+    pub name: Option<String>,
+    /// Drawn in order, so later layers are in front.
+    pub layers: Vec<StaticLayer>,
+    pub settings: StaticSettings,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    name: Option<String>,
+    layers: Vec<ManifestLayer>,
+    #[serde(default)]
+    settings: StaticSettings,
+}
+
+#[derive(Deserialize)]
+struct ManifestLayer {
+    /// Relative to the manifest.
+    image: PathBuf,
+    #[serde(default)]
+    offset: Vec2,
+    #[serde(default)]
+    talking: Option<bool>,
+    #[serde(default)]
+    blinking: Option<bool>,
+}
+
+/// Whether a file looks like a static puppet, going by its extension.
+pub fn is_static_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(ext)))
+}
+
+impl StaticPuppet {
+    /// Loads a puppet from a JSON manifest, or makes one from a lone image.
+    pub fn load(path: &Path) -> Result<StaticPuppet, StaticError> {
+        let is_manifest = path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("json"));
+        if !is_manifest {
+            let image =
+                StaticImage::open(path).map_err(|e| StaticError::Layer(path.to_owned(), e))?;
+            return Ok(StaticPuppet {
+                name: None,
+                layers: vec![StaticLayer {
+                    image: Arc::new(image),
+                    offset: Vec2::ZERO,
+                    talking: None,
+                    blinking: None,
+                }],
+                settings: StaticSettings::default(),
+            });
+        }
+
+        let manifest: Manifest = serde_json::from_slice(&fs::read(path)?)?;
+        if manifest.layers.is_empty() {
+            return Err(StaticError::NoLayers);
+        }
+
+        let folder = path.parent().unwrap_or(Path::new(""));
+        let layers = manifest
+            .layers
+            .into_iter()
+            .map(|layer| {
+                let image_path = folder.join(&layer.image);
+                let image = StaticImage::open(&image_path)
+                    .map_err(|e| StaticError::Layer(layer.image, e))?;
+                Ok(StaticLayer {
+                    image: Arc::new(image),
+                    offset: layer.offset,
+                    talking: layer.talking,
+                    blinking: layer.blinking,
+                })
+            })
+            .collect::<Result<_, StaticError>>()?;
+
+        Ok(StaticPuppet {
+            name: manifest.name,
+            layers,
+            settings: manifest.settings,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+use crate::tracker::Inputs;
+
+use super::{StaticSettings, MAX_TALK_THRESHOLD};
+
+/// Which layers a static puppet shows this frame, and where it's moved to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StaticPose {
+    pub talking: bool,
+    pub blinking: bool,
+    /// In image pixels, with y down.
+    pub offset: Vec2,
+}
+
+/// Works out a static puppet's pose from its inputs over time.
+#[derive(Debug, Default)]
+pub struct StaticMotion {
+    // Time since the last timed blink started.
+    since_blink: f32,
+    // How far through the current hop, from 0 to 1.
+    hop: f32,
+    elapsed: f32,
+}
+
+impl StaticMotion {
+    pub fn update(&mut self, dt: f32, settings: &StaticSettings, inputs: &Inputs) -> StaticPose {
+        self.elapsed += dt;
+
+        let threshold = settings.talk_threshold.clamp(0.0, MAX_TALK_THRESHOLD);
+        let level = settings.talk_input.value(inputs).unwrap_or(0.0);
+        let talking = level > threshold;
+
+        let blinking = match &settings.blink_input {
+            Some(input) => input.value(inputs).unwrap_or(0.0) > settings.blink_threshold,
+            None => {
+                self.since_blink += dt;
+                if self.since_blink > settings.blink_interval.max(settings.blink_duration) {
+                    self.since_blink = 0.0;
+                }
+                self.since_blink < settings.blink_duration
+            }
+        };
+
+        // A hop that's started is finished even if talking stops, so the
+        // puppet never hangs in the air.
+        if talking || self.hop > 0.0 {
+            self.hop += dt * settings.bounce_speed;
+            if self.hop >= 1.0 {
+                self.hop = if talking { self.hop.fract() } else { 0.0 };
+            }
+        }
+        let height = (self.hop * PI).sin() * settings.bounce;
+
+        // Shaking grows with how far past the threshold the input is.
+        let strength = ((level - threshold) / (1.0 - threshold)).clamp(0.0, 1.0) * settings.shake;
+        let t = self.elapsed;
+        let shake = Vec2::new(
+            (t * 41.0).sin() * (t * 17.0).cos(),
+            (t * 37.0).cos() * (t * 23.0).sin(),
+        ) * strength;
+
+        StaticPose {
+            talking,
+            blinking,
+            offset: Vec2::new(0.0, -height) + shake,
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use egui_wgpu::wgpu::{self, util::DeviceExt};
+use glam::{UVec2, Vec2};
+use inox2d::math::camera::Camera;
+
+use super::{StaticPose, StaticPuppet};
+
+struct LayerTexture {
+    bind_group: wgpu::BindGroup,
+    vertices: wgpu::Buffer,
+    size: Vec2,
+}
+
+/// Renders a static puppet, seen through a camera like an Inochi2D puppet.
+pub struct StaticRenderer {
+    pub camera: Camera,
+    viewport: UVec2,
+    pipeline: wgpu::RenderPipeline,
+    layers: Vec<LayerTexture>,
+}
+
+impl StaticRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        puppet: &StaticPuppet,
+        viewport: UVec2,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Static Puppet Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("static.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Static Puppet Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Static Puppet Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Static Puppet Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: 16,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Static Puppet Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let layers = puppet
+            .layers
+            .iter()
+            .map(|layer| {
+                let image = &layer.image;
+                let texture = device.create_texture_with_data(
+                    queue,
+                    &wgpu::TextureDescriptor {
+                        label: Some("Static Puppet Layer"),
+                        size: wgpu::Extent3d {
+                            width: image.width,
+                            height: image.height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: wgpu::TextureFormat::Rgba8Unorm,
+                        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    },
+                    &image.pixels,
+                );
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Static Puppet Bind Group"),
+                    layout: &bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                    ],
+                });
+
+                let vertices = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Static Puppet Vertices"),
+                    size: 64,
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+
+                LayerTexture {
+                    bind_group,
+                    vertices,
+                    size: Vec2::new(image.width as f32, image.height as f32),
+                }
+            })
+            .collect();
+
+        Self {
+            camera: Camera::default(),
+            viewport,
+            pipeline,
+            layers,
+        }
+    }
+
+    pub fn resize(&mut self, viewport: UVec2) {
+        self.viewport = viewport;
+    }
+
+    /// Maps a point on the puppet, in model units, to clip space.
+    fn to_clip(&self, point: Vec2) -> Vec2 {
+        let screen = Vec2::from_angle(self.camera.rotation)
+            .rotate((point + self.camera.position) * self.camera.scale);
+        let half = self.viewport.as_vec2() / 2.0;
+        Vec2::new(screen.x / half.x, -screen.y / half.y)
+    }
+
+    pub fn render(
+        &self,
+        queue: &wgpu::Queue,
+        device: &wgpu::Device,
+        puppet: &StaticPuppet,
+        pose: &StaticPose,
+        target: &wgpu::TextureView,
+    ) {
+        let scale = puppet.settings.scale;
+        let visible: Vec<_> = puppet
+            .layers
+            .iter()
+            .zip(&self.layers)
+            .filter(|(layer, _)| layer.visible(pose))
+            .collect();
+
+        for (layer, texture) in &visible {
+            let middle = (layer.offset + pose.offset) * scale;
+            let half = texture.size * scale / 2.0;
+            // Corners in strip order, each followed by its texture coordinate.
+            let mut vertices = [0.0f32; 16];
+            for (ind, (corner, uv)) in [
+                (Vec2::new(-half.x, -half.y), Vec2::new(0.0, 0.0)),
+                (Vec2::new(-half.x, half.y), Vec2::new(0.0, 1.0)),
+                (Vec2::new(half.x, -half.y), Vec2::new(1.0, 0.0)),
+                (Vec2::new(half.x, half.y), Vec2::new(1.0, 1.0)),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let clip = self.to_clip(middle + corner);
+                vertices[ind * 4..ind * 4 + 4].copy_from_slice(&[clip.x, clip.y, uv.x, uv.y]);
+            }
+            queue.write_buffer(&texture.vertices, 0, bytemuck::cast_slice(&vertices));
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Static Puppet Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Static Puppet Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..wgpu::RenderPassDescriptor::default()
+            });
+            pass.set_pipeline(&self.pipeline);
+            for (_, texture) in &visible {
+                pass.set_bind_group(0, &texture.bind_group, &[]);
+                pass.set_vertex_buffer(0, texture.vertices.slice(..));
+                pass.draw(0..4, 0..1);
+            }
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
@@ -0,0 +1,31 @@
+// Draws one image layer of a static puppet, placed on the CPU.
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var layer: texture_2d<f32>;
+@group(0) @binding(1)
+var layer_sampler: sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(in.position, 0.0, 1.0);
+    out.uv = in.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Images are stored straight, but puppets render premultiplied.
+    let color = textureSample(layer, layer_sampler, in.uv);
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
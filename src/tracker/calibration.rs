@@ -0,0 +1,43 @@
+//! A neutral pose captured from the tracker, so bones read relative to how
+//! the user was sitting when it was taken rather than to the tracker's own
+//! idea of straight ahead.
+
+use std::collections::HashMap;
+
+use glam::{Quat, Vec3};
+use mahou_vmc::VmcData;
+
+#[derive(Debug, Default)]
+pub struct Calibration {
+    neutral: HashMap<String, (Vec3, Quat)>,
+}
+
+impl Calibration {
+    /// Takes every bone's current position and rotation as its neutral.
+    pub fn capture(&mut self, data: &VmcData) {
+        self.neutral = data
+            .bones
+            .iter()
+            .map(|(name, bone)| (name.clone(), (bone.0, bone.1)))
+            .collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.neutral.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neutral.is_empty()
+    }
+
+    /// A bone's position and rotation relative to its neutral, if it has one.
+    pub fn relative(&self, name: &str, position: Vec3, rotation: Quat) -> (Vec3, Quat) {
+        match self.neutral.get(name) {
+            Some((neutral_position, neutral_rotation)) => (
+                position - *neutral_position,
+                neutral_rotation.inverse() * rotation,
+            ),
+            None => (position, rotation),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use glam::{EulerRot, Vec2};
+use mahou_vmc::VmcData;
+
+use crate::audio::AudioSystem;
+
+use super::{BindingKind, Calibration, Generators, InputBoneKind, InputKind};
+
+/// Everything a binding can read its input from.
+pub struct Inputs<'a> {
+    pub tracker: &'a VmcData,
+    pub calibration: &'a Calibration,
+    pub generators: &'a Generators,
+    pub audio: &'a AudioSystem,
+    /// Parameter values already computed from their bindings.
+    pub parameters: &'a HashMap<String, Vec2>,
+}
+
+pub fn map_value(val: f32, (x1, x2): (f32, f32), (y1, y2): (f32, f32)) -> f32 {
+    (val - x1) * (y2 - y1) / (x2 - x1) + y1
+}
+
+impl InputKind {
+    /// Reads the raw value of this input, or `None` if it isn't currently
+    /// available.
+    pub fn value(&self, inputs: &Inputs) -> Option<f32> {
+        let data = inputs.tracker;
+        match self {
+            InputKind::None => Some(0.0),
+            InputKind::Blendshape(x) => data.blends.get(x).copied(),
+            InputKind::Bone(x, v) => {
+                let bone = data.bones.get(x).copied()?;
+                let (position, rotation) = inputs.calibration.relative(x, bone.0, bone.1);
+                let (yaw, pitch, roll) = rotation.to_euler(EulerRot::YXZ);
+                Some(match v {
+                    InputBoneKind::X => position.x,
+                    InputBoneKind::Y => position.y,
+                    InputBoneKind::Z => position.z,
+                    InputBoneKind::Roll => roll.to_degrees(),
+                    InputBoneKind::Pitch => pitch.to_degrees(),
+                    InputBoneKind::Yaw => yaw.to_degrees(),
+                })
+            }
+            InputKind::Generator(kind) => inputs.generators.value(*kind),
+            InputKind::Audio(kind) => inputs.audio.value(*kind),
+            InputKind::Parameter(name, axis) => inputs.parameters.get(name).map(|x| axis.get(*x)),
+        }
+    }
+}
+
+impl BindingKind {
+    /// Computes the parameter value for this binding, before smoothing.
+    pub fn evaluate(&self, inputs: &Inputs) -> f32 {
+        match self {
+            // Expressions aren't implemented yet, so leave the parameter
+            // at rest rather than bringing down the render loop.
+            BindingKind::Expr => 0.0,
+            BindingKind::Simple {
+                input,
+                input_range,
+                output_range,
+                fallback,
+                ..
+            } => {
+                if let Some(kind) = fallback.filter(|_| inputs.generators.is_untracked(input)) {
+                    if let Some(val) = inputs.generators.fallback_value(kind) {
+                        return map_value(val, kind.range(), *output_range);
+                    }
+                }
+
+                let initial = input.value(inputs).unwrap_or(0.0);
+                map_value(initial, *input_range, *output_range)
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Procedural inputs that keep a puppet alive when tracking alone doesn't,
+//! such as blinking and breathing.
+
+use std::{collections::HashMap, f32::consts::TAU};
+
+use mahou_vmc::VmcData;
+use serde::{Deserialize, Serialize};
+
+use super::InputKind;
+
+/// Seconds a tracker input can go without changing before bindings falling
+/// back to a generator treat it as lost.
+const UNTRACKED_AFTER: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GeneratorKind {
+    Blink,
+    Breath,
+    SwayX,
+    SwayY,
+    SwayRoll,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 5] = [
+        GeneratorKind::Blink,
+        GeneratorKind::Breath,
+        GeneratorKind::SwayX,
+        GeneratorKind::SwayY,
+        GeneratorKind::SwayRoll,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            GeneratorKind::Blink => "Auto Blink",
+            GeneratorKind::Breath => "Breathing",
+            GeneratorKind::SwayX => "Idle Sway X",
+            GeneratorKind::SwayY => "Idle Sway Y",
+            GeneratorKind::SwayRoll => "Idle Sway Roll",
+        }
+    }
+
+    /// The values this generator goes between.
+    pub fn range(&self) -> (f32, f32) {
+        match self {
+            GeneratorKind::Blink | GeneratorKind::Breath => (0.0, 1.0),
+            GeneratorKind::SwayX | GeneratorKind::SwayY | GeneratorKind::SwayRoll => (-1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorMode {
+    Off,
+    Always,
+    /// Only stands in for tracking that's been lost. Bound directly, that's
+    /// while no tracking data is received at all, and as a binding's
+    /// fallback, while its own input is missing or stops changing.
+    WhenUntracked,
+}
+
+impl GeneratorMode {
+    pub const ALL: [GeneratorMode; 3] = [
+        GeneratorMode::Off,
+        GeneratorMode::Always,
+        GeneratorMode::WhenUntracked,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            GeneratorMode::Off => "Off",
+            GeneratorMode::Always => "Always",
+            GeneratorMode::WhenUntracked => "When Untracked",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BlinkSettings {
+    pub mode: GeneratorMode,
+    /// Average seconds between blinks.
+    pub interval: f32,
+    /// Seconds a blink takes from open to closed to open.
+    pub duration: f32,
+    /// Chance of a second blink following right after the first.
+    pub double_chance: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct BreathSettings {
+    pub mode: GeneratorMode,
+    /// Seconds per breath.
+    pub period: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct SwaySettings {
+    pub mode: GeneratorMode,
+    /// Roughly how many direction changes happen per second.
+    pub speed: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct GeneratorSettings {
+    pub blink: BlinkSettings,
+    pub breath: BreathSettings,
+    pub sway: SwaySettings,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            blink: BlinkSettings {
+                mode: GeneratorMode::WhenUntracked,
+                interval: 4.0,
+                duration: 0.18,
+                double_chance: 0.1,
+            },
+            breath: BreathSettings {
+                mode: GeneratorMode::Always,
+                period: 4.5,
+            },
+            sway: SwaySettings {
+                mode: GeneratorMode::WhenUntracked,
+                speed: 0.25,
+            },
+        }
+    }
+}
+
+impl GeneratorSettings {
+    fn mode(&self, kind: GeneratorKind) -> GeneratorMode {
+        match kind {
+            GeneratorKind::Blink => self.blink.mode,
+            GeneratorKind::Breath => self.breath.mode,
+            GeneratorKind::SwayX | GeneratorKind::SwayY | GeneratorKind::SwayRoll => self.sway.mode,
+        }
+    }
+}
+
+/// A small xorshift generator, as blink timing doesn't need anything better.
+struct Rng(u32);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+fn hash(x: i32, seed: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x27d4_eb2d) ^ seed.wrapping_mul(0x1656_67b1);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h
+}
+
+/// One dimensional Perlin noise, roughly in the range -1 to 1.
+fn perlin(x: f32, seed: u32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let gradient = |i: i32| (hash(i, seed) >> 8) as f32 / (1 << 23) as f32 - 1.0;
+
+    let a = gradient(cell as i32) * t;
+    let b = gradient(cell as i32 + 1) * (t - 1.0);
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+
+    // Scaled so the extremes of 1D gradient noise reach about +-1.
+    (a + (b - a) * fade) * 2.0
+}
+
+/// A tracker input's last value, and when it changed to it.
+struct Change {
+    value: [f32; 7],
+    time: f32,
+}
+
+pub struct Generators {
+    pub settings: GeneratorSettings,
+    tracked: bool,
+    time: f32,
+    rng: Rng,
+    // When each blendshape and bone last moved, for telling which inputs are
+    // still tracked.
+    blends: HashMap<String, Change>,
+    bones: HashMap<String, Change>,
+
+    next_blink: f32,
+    blink_start: Option<f32>,
+    pending_double: bool,
+}
+
+impl Default for Generators {
+    fn default() -> Self {
+        let mut out = Self {
+            settings: GeneratorSettings::default(),
+            tracked: false,
+            time: 0.0,
+            rng: Rng(0x9e37_79b9),
+            blends: HashMap::new(),
+            bones: HashMap::new(),
+            next_blink: 0.0,
+            blink_start: None,
+            pending_double: false,
+        };
+        out.schedule_blink();
+        out
+    }
+}
+
+impl Generators {
+    /// Advances every generator, where `tracked` is whether tracking data is
+    /// currently being received and `data` is the latest of it.
+    pub fn update(&mut self, dt: f32, tracked: bool, data: &VmcData) {
+        self.tracked = tracked;
+        self.time += dt;
+
+        let time = self.time;
+        let note =
+            |changes: &mut HashMap<String, Change>, name: &String, value: [f32; 7]| match changes
+                .get_mut(name)
+            {
+                Some(change) if change.value == value => {}
+                Some(change) => *change = Change { value, time },
+                None => {
+                    changes.insert(name.clone(), Change { value, time });
+                }
+            };
+        self.blends.retain(|name, _| data.blends.contains_key(name));
+        for (name, value) in &data.blends {
+            note(
+                &mut self.blends,
+                name,
+                [*value, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            );
+        }
+        self.bones.retain(|name, _| data.bones.contains_key(name));
+        for (name, (pos, rot)) in &data.bones {
+            let [x, y, z] = pos.to_array();
+            let [i, j, k, w] = rot.to_array();
+            note(&mut self.bones, name, [x, y, z, i, j, k, w]);
+        }
+
+        if let Some(start) = self.blink_start {
+            if self.time - start >= self.settings.blink.duration {
+                self.blink_start = None;
+                if self.pending_double {
+                    self.pending_double = false;
+                    self.next_blink = self.time + 0.1;
+                } else {
+                    self.schedule_blink();
+                }
+            }
+        } else if self.time >= self.next_blink {
+            self.blink_start = Some(self.time);
+        }
+    }
+
+    fn schedule_blink(&mut self) {
+        // Exponentially distributed waits, clamped so blinks are neither
+        // machine-gunned nor absent for too long.
+        let interval = self.settings.blink.interval.max(0.5);
+        let wait = -interval * (1.0 - self.rng.next_f32()).ln();
+        self.next_blink = self.time + wait.clamp(interval * 0.3, interval * 3.0);
+        self.pending_double = self.rng.next_f32() < self.settings.blink.double_chance;
+    }
+
+    /// Whether a tracker input has no value, or hasn't changed in a while.
+    /// Inputs that don't come from the tracker are never untracked.
+    pub fn is_untracked(&self, input: &InputKind) -> bool {
+        let change = match input {
+            InputKind::Blendshape(name) => self.blends.get(name),
+            InputKind::Bone(name, _) => self.bones.get(name),
+            _ => return false,
+        };
+        change.map_or(true, |x| self.time - x.time >= UNTRACKED_AFTER)
+    }
+
+    pub fn is_active(&self, kind: GeneratorKind) -> bool {
+        match self.settings.mode(kind) {
+            GeneratorMode::Off => false,
+            GeneratorMode::Always => true,
+            GeneratorMode::WhenUntracked => !self.tracked,
+        }
+    }
+
+    /// The current value of a generator, or `None` if it isn't active.
+    ///
+    /// Blinking and breathing go from 0 to 1, while sway goes from -1 to 1.
+    pub fn value(&self, kind: GeneratorKind) -> Option<f32> {
+        self.is_active(kind).then(|| self.generate(kind))
+    }
+
+    /// The value of a generator standing in for an untracked input, or `None`
+    /// if it's off.
+    pub fn fallback_value(&self, kind: GeneratorKind) -> Option<f32> {
+        (self.settings.mode(kind) != GeneratorMode::Off).then(|| self.generate(kind))
+    }
+
+    fn generate(&self, kind: GeneratorKind) -> f32 {
+        match kind {
+            GeneratorKind::Blink => self.blink_value(),
+            GeneratorKind::Breath => {
+                let period = self.settings.breath.period.max(0.1);
+                (1.0 - (self.time / period * TAU).cos()) / 2.0
+            }
+            GeneratorKind::SwayX => perlin(self.time * self.settings.sway.speed, 1),
+            GeneratorKind::SwayY => perlin(self.time * self.settings.sway.speed, 2),
+            GeneratorKind::SwayRoll => perlin(self.time * self.settings.sway.speed, 3),
+        }
+    }
+
+    fn blink_value(&self) -> f32 {
+        let Some(start) = self.blink_start else {
+            return 0.0;
+        };
+
+        // Eyes close quicker than they open again.
+        let duration = self.settings.blink.duration.max(0.01);
+        let t = ((self.time - start) / duration).clamp(0.0, 1.0);
+        let closing = 0.35;
+        let x = if t < closing {
+            t / closing
+        } else {
+            1.0 - (t - closing) / (1.0 - closing)
+        };
+
+        x * x * (3.0 - 2.0 * x)
+    }
+}
@@ -1,10 +1,138 @@
+mod calibration;
+mod eval;
+mod generators;
+mod order;
+mod smoothing;
 mod system;
+mod trigger;
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, mem::discriminant};
 
-pub use system::TrackerSystem;
+use glam::Vec2;
+use mahou_vmc::VmcData;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+use crate::{app::model::Stored, audio::AudioKind};
+
+pub use calibration::Calibration;
+pub use eval::{map_value, Inputs};
+pub use generators::{GeneratorKind, GeneratorMode, Generators};
+pub use order::EvaluationOrder;
+pub use smoothing::Smoothing;
+pub use system::{ConnectError, TrackerSystem};
+pub use trigger::{Comparison, Trigger, TriggerState, Triggers};
+
+/// Swaps the sides in a left/right-symmetrical name, such as
+/// `EyeBlinkLeft` or `Eye_R`, returning `None` if the name has no side.
+pub fn mirror_name(name: &str) -> Option<String> {
+    const WORDS: [(&str, &str); 3] = [("Left", "Right"), ("left", "right"), ("LEFT", "RIGHT")];
+    const SUFFIXES: [(&str, &str); 4] = [("_L", "_R"), (".L", ".R"), ("_l", "_r"), (".l", ".r")];
+
+    let mut out = String::with_capacity(name.len() + 1);
+    let mut changed = false;
+    let mut prev = None;
+    let mut ind = 0;
+
+    'outer: while let Some(ch) = name[ind..].chars().next() {
+        for (a, b) in WORDS {
+            for (from, to) in [(a, b), (b, a)] {
+                let rest = &name[ind..];
+                if rest.starts_with(from)
+                    && starts_word(prev, from)
+                    && ends_word(from, rest[from.len()..].chars().next())
+                {
+                    out.push_str(to);
+                    ind += from.len();
+                    prev = from.chars().last();
+                    changed = true;
+                    continue 'outer;
+                }
+            }
+        }
+
+        out.push(ch);
+        ind += ch.len_utf8();
+        prev = Some(ch);
+    }
+
+    if changed {
+        return Some(out);
+    }
+
+    // Short markers are only trusted at the end of a name, as `_L` could
+    // just as well be the start of `_Lip`.
+    for (a, b) in SUFFIXES {
+        for (from, to) in [(a, b), (b, a)] {
+            if let Some(stem) = name.strip_suffix(from) {
+                return Some(format!("{}{}", stem, to));
+            }
+        }
+    }
+
+    None
+}
+
+fn is_separator(ch: char) -> bool {
+    matches!(ch, '_' | '.' | ':' | ' ' | '-')
+}
+
+/// Whether `word` can start after `prev`: at the start of the name, after
+/// a separator, or where the case changes, as in `EyeLeft`. Lowercase words
+/// have no case change to go by, so `Brightness` has no side.
+fn starts_word(prev: Option<char>, word: &str) -> bool {
+    let Some(prev) = prev else {
+        return true;
+    };
+    let capital = word.starts_with(char::is_uppercase);
+    is_separator(prev) || (capital && (prev.is_lowercase() || prev.is_ascii_digit()))
+}
+
+/// Whether `word` can end before `next`, so `Lefty` has no side but
+/// `LeftEye` and `LEFT_EYE` do.
+fn ends_word(word: &str, next: Option<char>) -> bool {
+    let Some(next) = next else {
+        return true;
+    };
+    let upper = word.chars().all(char::is_uppercase);
+    if upper {
+        !next.is_alphabetic() || next.is_lowercase()
+    } else {
+        !next.is_lowercase()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 2] = [Axis::X, Axis::Y];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Axis::X => "X",
+            Axis::Y => "Y",
+        }
+    }
+
+    pub fn get(&self, value: Vec2) -> f32 {
+        match self {
+            Axis::X => value.x,
+            Axis::Y => value.y,
+        }
+    }
+
+    pub fn set(&self, value: &mut Vec2, to: f32) {
+        match self {
+            Axis::X => value.x = to,
+            Axis::Y => value.y = to,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputBoneKind {
     X,
     Y,
@@ -27,14 +155,51 @@ impl InputBoneKind {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputKind {
     None,
     Blendshape(String),
     Bone(String, InputBoneKind),
+    Generator(GeneratorKind),
+    Audio(AudioKind),
+    /// Another parameter's value as computed by its own binding this frame.
+    Parameter(String, Axis),
 }
 
 impl InputKind {
+    /// Every input that can currently be read, given the tracker's data.
+    pub fn available(data: &VmcData) -> Vec<InputKind> {
+        let mut out = vec![InputKind::None];
+        for k in data.blends.keys() {
+            out.push(InputKind::Blendshape(k.clone()));
+        }
+        for k in data.bones.keys() {
+            use InputBoneKind::*;
+            for i in [X, Y, Z, Pitch, Yaw, Roll] {
+                out.push(InputKind::Bone(k.clone(), i));
+            }
+        }
+        for kind in GeneratorKind::ALL {
+            out.push(InputKind::Generator(kind));
+        }
+        for kind in AudioKind::ALL {
+            out.push(InputKind::Audio(kind));
+        }
+        out
+    }
+
+    /// Inputs reading the computed value of every parameter in `bindings`.
+    pub fn parameters(bindings: &ParamBindings) -> impl Iterator<Item = InputKind> + '_ {
+        bindings.iter().flat_map(|(name, binding)| {
+            let axes: &[Axis] = match binding {
+                ParamBinding::OneDim(_) => &[Axis::X],
+                ParamBinding::TwoDim(_) => &Axis::ALL,
+            };
+            axes.iter()
+                .map(|axis| InputKind::Parameter(name.clone(), *axis))
+        })
+    }
+
     pub fn name(&self) -> String {
         match self {
             InputKind::None => "<none>".to_string(),
@@ -42,11 +207,33 @@ impl InputKind {
             InputKind::Bone(name, kind) => {
                 format!("{} ({})", name, kind.name())
             }
+            InputKind::Generator(kind) => kind.name().to_string(),
+            InputKind::Audio(kind) => kind.name().to_string(),
+            InputKind::Parameter(name, axis) => {
+                format!("Parameter: {} ({})", name, axis.name())
+            }
+        }
+    }
+
+    pub fn mirrored(&self) -> InputKind {
+        match self {
+            InputKind::None => InputKind::None,
+            InputKind::Blendshape(name) => {
+                InputKind::Blendshape(mirror_name(name).unwrap_or_else(|| name.clone()))
+            }
+            InputKind::Bone(name, kind) => {
+                InputKind::Bone(mirror_name(name).unwrap_or_else(|| name.clone()), *kind)
+            }
+            InputKind::Generator(kind) => InputKind::Generator(*kind),
+            InputKind::Audio(kind) => InputKind::Audio(*kind),
+            InputKind::Parameter(name, axis) => {
+                InputKind::Parameter(mirror_name(name).unwrap_or_else(|| name.clone()), *axis)
+            }
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BindingKind {
     Expr,
     Simple {
@@ -54,6 +241,10 @@ pub enum BindingKind {
         input_range: (f32, f32),
         output_range: (f32, f32),
         dampen: f32,
+        /// Generator standing in while the input is untracked, mapped from
+        /// its own range onto the output range.
+        #[serde(default)]
+        fallback: Option<GeneratorKind>,
     },
 }
 
@@ -64,15 +255,56 @@ impl BindingKind {
             input_range: (-30.0, 30.0),
             output_range: (-1.0, 1.0),
             dampen: 0.0,
+            fallback: None,
         }
     }
 
     pub const fn expr() -> BindingKind {
         BindingKind::Expr
     }
+
+    /// The input read by this binding, if it reads one.
+    pub fn input(&self) -> Option<&InputKind> {
+        match self {
+            BindingKind::Expr => None,
+            BindingKind::Simple { input, .. } => Some(input),
+        }
+    }
+
+    pub fn dampen(&self) -> f32 {
+        match self {
+            BindingKind::Expr => 0.0,
+            BindingKind::Simple { dampen, .. } => *dampen,
+        }
+    }
+
+    /// Returns this binding for the opposite side of the model, optionally
+    /// flipping the direction of the output.
+    pub fn mirrored(&self, negate: bool) -> BindingKind {
+        match self {
+            BindingKind::Expr => BindingKind::Expr,
+            BindingKind::Simple {
+                input,
+                input_range,
+                output_range,
+                dampen,
+                fallback,
+            } => BindingKind::Simple {
+                input: input.mirrored(),
+                input_range: *input_range,
+                output_range: if negate {
+                    (-output_range.0, -output_range.1)
+                } else {
+                    *output_range
+                },
+                dampen: *dampen,
+                fallback: *fallback,
+            },
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ParamBinding {
     OneDim(Option<BindingKind>),
     TwoDim(Option<(BindingKind, BindingKind)>),
@@ -105,6 +337,87 @@ impl ParamBinding {
             ParamBinding::TwoDim(v) => v.is_none(),
         }
     }
+
+    /// Whether both bindings are for parameters with the same number of axes.
+    pub fn same_dimension(&self, other: &ParamBinding) -> bool {
+        discriminant(self) == discriminant(other)
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = &BindingKind> {
+        let (first, second) = match self {
+            ParamBinding::OneDim(v) => (v.as_ref(), None),
+            ParamBinding::TwoDim(Some((x, y))) => (Some(x), Some(y)),
+            ParamBinding::TwoDim(None) => (None, None),
+        };
+        first.into_iter().chain(second)
+    }
+
+    pub fn kinds_mut(&mut self) -> impl Iterator<Item = &mut BindingKind> {
+        let (first, second) = match self {
+            ParamBinding::OneDim(v) => (v.as_mut(), None),
+            ParamBinding::TwoDim(Some((x, y))) => (Some(x), Some(y)),
+            ParamBinding::TwoDim(None) => (None, None),
+        };
+        first.into_iter().chain(second)
+    }
+
+    pub fn mirrored(&self, negate: bool) -> ParamBinding {
+        match self {
+            ParamBinding::OneDim(v) => ParamBinding::OneDim(v.as_ref().map(|x| x.mirrored(negate))),
+            ParamBinding::TwoDim(v) => ParamBinding::TwoDim(
+                v.as_ref()
+                    .map(|(x, y)| (x.mirrored(negate), y.mirrored(negate))),
+            ),
+        }
+    }
 }
 
 pub type ParamBindings = BTreeMap<String, ParamBinding>;
+
+impl Stored for ParamBindings {
+    const FILE: &'static str = "bindings.json";
+    const NAME: &'static str = "bindings";
+    type Data = ParamBindings;
+
+    fn from_stored(bindings: ParamBindings) -> Self {
+        bindings
+    }
+
+    fn stored(&self) -> &ParamBindings {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mirror_name;
+
+    #[test]
+    fn mirrors_side_words() {
+        assert_eq!(
+            mirror_name("EyeBlinkLeft").as_deref(),
+            Some("EyeBlinkRight")
+        );
+        assert_eq!(mirror_name("RightEye").as_deref(), Some("LeftEye"));
+        assert_eq!(mirror_name("left_eye").as_deref(), Some("right_eye"));
+        assert_eq!(mirror_name("Eye:Right").as_deref(), Some("Eye:Left"));
+        assert_eq!(mirror_name("EYE_LEFT").as_deref(), Some("EYE_RIGHT"));
+        assert_eq!(mirror_name("Arm Left 2").as_deref(), Some("Arm Right 2"));
+    }
+
+    #[test]
+    fn mirrors_short_suffixes() {
+        assert_eq!(mirror_name("Eye_L").as_deref(), Some("Eye_R"));
+        assert_eq!(mirror_name("Eye.r").as_deref(), Some("Eye.l"));
+    }
+
+    #[test]
+    fn ignores_sides_inside_words() {
+        assert_eq!(mirror_name("Brightness"), None);
+        assert_eq!(mirror_name("Copyright"), None);
+        assert_eq!(mirror_name("Upright"), None);
+        assert_eq!(mirror_name("Lefty"), None);
+        assert_eq!(mirror_name("leftover"), None);
+        assert_eq!(mirror_name("_Lip"), None);
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::{InputKind, ParamBindings};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+/// The order bindings have to be evaluated in, so that a parameter chained
+/// to another is only computed once the other has been.
+#[derive(Debug, Default)]
+pub struct EvaluationOrder<'a> {
+    pub order: Vec<&'a str>,
+    /// Parameters chained to themselves, directly or through others. These
+    /// have no sensible value, so are left out of `order`.
+    pub cyclic: BTreeSet<&'a str>,
+}
+
+impl<'a> EvaluationOrder<'a> {
+    pub fn new(bindings: &'a ParamBindings) -> Self {
+        let mut out = Self::default();
+        let mut marks = HashMap::new();
+        let mut stack = Vec::new();
+
+        for (name, binding) in bindings {
+            if binding.is_bound() {
+                out.visit(bindings, name, &mut marks, &mut stack);
+            }
+        }
+
+        out
+    }
+
+    /// The bound parameters whose values `param`'s binding reads.
+    fn dependencies(bindings: &'a ParamBindings, param: &str) -> impl Iterator<Item = &'a str> {
+        bindings
+            .get(param)
+            .into_iter()
+            .flat_map(|x| x.kinds())
+            .filter_map(move |kind| match kind.input() {
+                Some(InputKind::Parameter(name, _)) => bindings.get_key_value(name),
+                _ => None,
+            })
+            .filter(|(_, binding)| binding.is_bound())
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn visit(
+        &mut self,
+        bindings: &'a ParamBindings,
+        param: &'a str,
+        marks: &mut HashMap<&'a str, Mark>,
+        stack: &mut Vec<&'a str>,
+    ) {
+        match marks.get(param) {
+            Some(Mark::Done) => return,
+            Some(Mark::Visiting) => {
+                // Everything visited since first reaching the parameter again
+                // leads back to it.
+                let start = stack
+                    .iter()
+                    .rposition(|x| *x == param)
+                    .expect("parameters being visited are on the stack");
+                self.cyclic.extend(&stack[start..]);
+                return;
+            }
+            None => {}
+        }
+
+        marks.insert(param, Mark::Visiting);
+        stack.push(param);
+        for dependency in Self::dependencies(bindings, param) {
+            self.visit(bindings, dependency, marks, stack);
+        }
+        stack.pop();
+        marks.insert(param, Mark::Done);
+
+        if !self.cyclic.contains(param) {
+            self.order.push(param);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use glam::Vec2;
+
+/// Per-parameter state for the exponential smoothing controlled by a
+/// binding's `dampen` factor.
+#[derive(Debug, Default)]
+pub struct Smoothing {
+    previous: HashMap<String, Vec2>,
+}
+
+impl Smoothing {
+    /// Moves the parameter towards `target`, where `dampen` is the fraction
+    /// of the previous value kept every 1/60th of a second.
+    pub fn apply(&mut self, param: &str, target: Vec2, dampen: Vec2, dt: f32) -> Vec2 {
+        let Some(previous) = self.previous.get_mut(param) else {
+            self.previous.insert(param.to_owned(), target);
+            return target;
+        };
+
+        let keep = Vec2::new(
+            dampen.x.clamp(0.0, 0.99).powf(dt * 60.0),
+            dampen.y.clamp(0.0, 0.99).powf(dt * 60.0),
+        );
+        *previous = target + (*previous - target) * keep;
+        *previous
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+    }
+}
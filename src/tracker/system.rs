@@ -1,27 +1,44 @@
 use std::{
-    io::ErrorKind,
-    net::{ToSocketAddrs, UdpSocket},
-    panic::{catch_unwind, UnwindSafe},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    panic::catch_unwind,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
     thread::{spawn, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use mahou_vmc::VmcData;
 use thiserror::Error;
 
+use crate::audio::AudioSystem;
+
+use super::{Calibration, Generators};
+
+/// How long after the last packet tracking is considered lost.
+const TRACKING_TIMEOUT: Duration = Duration::from_secs(1);
+
 pub struct TrackerSystem {
     join_handle: Option<JoinHandle<()>>,
     abort: Arc<AtomicBool>,
     data: Arc<Mutex<VmcData>>,
+    last_packet: Arc<Mutex<Option<Instant>>>,
+    // Where the tracker last connected to, for reconnecting.
+    address: Option<SocketAddr>,
+    generators: Generators,
+    audio: AudioSystem,
+    calibration: Calibration,
 }
 
 #[derive(Error, Debug)]
-#[error("tracker subsystem is already connected")]
-pub struct AlreadyConnectedError;
+pub enum ConnectError {
+    #[error("tracker subsystem is already connected")]
+    AlreadyConnected,
+    #[error("could not listen for tracking data: {0}")]
+    Bind(#[from] io::Error),
+}
 
 impl TrackerSystem {
     pub fn new() -> Self {
@@ -29,6 +46,11 @@ impl TrackerSystem {
             join_handle: None,
             abort: Arc::new(AtomicBool::new(false)),
             data: Arc::new(Mutex::new(VmcData::default())),
+            last_packet: Arc::new(Mutex::new(None)),
+            address: None,
+            generators: Generators::default(),
+            audio: AudioSystem::new(),
+            calibration: Calibration::default(),
         }
     }
 
@@ -36,6 +58,53 @@ impl TrackerSystem {
         &self.data
     }
 
+    pub fn generators(&self) -> &Generators {
+        &self.generators
+    }
+
+    pub fn generators_mut(&mut self) -> &mut Generators {
+        &mut self.generators
+    }
+
+    pub fn calibration(&self) -> &Calibration {
+        &self.calibration
+    }
+
+    /// Takes the pose currently tracked as neutral, so bindings read bones
+    /// relative to it.
+    pub fn calibrate(&mut self) {
+        self.calibration.capture(&self.data.lock().unwrap());
+    }
+
+    pub fn clear_calibration(&mut self) {
+        self.calibration.clear();
+    }
+
+    pub fn audio(&self) -> &AudioSystem {
+        &self.audio
+    }
+
+    pub fn audio_mut(&mut self) -> &mut AudioSystem {
+        &mut self.audio
+    }
+
+    /// Whether tracking data has been received recently.
+    pub fn receiving(&self) -> bool {
+        self.last_packet
+            .lock()
+            .unwrap()
+            .is_some_and(|x| x.elapsed() < TRACKING_TIMEOUT)
+    }
+
+    /// Advances everything that changes without the tracker's input.
+    pub fn update(&mut self, dt: f32) {
+        let tracked = self.receiving();
+        let data = self.data.lock().unwrap();
+        self.generators.update(dt, tracked, &data);
+        drop(data);
+        self.audio.update(dt);
+    }
+
     pub fn reset(&self) {
         let mut data = self.data.lock().unwrap();
         *data = VmcData::default();
@@ -48,6 +117,23 @@ impl TrackerSystem {
         }
     }
 
+    /// Stops listening without waiting for the network thread, which ends on
+    /// its own once its read times out.
+    pub fn stop(&mut self) {
+        if self.join_handle.take().is_some() {
+            self.abort.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Connects again to the address last connected to, if there is one.
+    pub fn reconnect(&mut self) -> Result<(), ConnectError> {
+        if let Some(addr) = self.address {
+            self.disconnect();
+            self.connect(addr)?;
+        }
+        Ok(())
+    }
+
     pub fn active(&self) -> bool {
         self.join_handle
             .as_ref()
@@ -55,41 +141,41 @@ impl TrackerSystem {
             .unwrap_or(false)
     }
 
-    pub fn connect<A: ToSocketAddrs + Send + UnwindSafe + 'static>(
-        &mut self,
-        addr: A,
-    ) -> Result<(), AlreadyConnectedError> {
+    pub fn connect<A: ToSocketAddrs>(&mut self, addr: A) -> Result<(), ConnectError> {
         if self.join_handle.is_some() {
-            return Err(AlreadyConnectedError);
+            return Err(ConnectError::AlreadyConnected);
         }
-        self.abort.store(false, Ordering::Relaxed);
+        self.address = addr.to_socket_addrs().ok().and_then(|mut x| x.next());
+
+        // Bound here so a port that's taken is reported to the caller.
+        let sock = UdpSocket::bind(addr)?;
+        sock.set_read_timeout(Some(Duration::from_millis(500)))
+            .expect("Setting timeout won't fail");
+
+        // A thread left to stop by itself keeps the flag it was given.
+        self.abort = Arc::new(AtomicBool::new(false));
 
         let data = Arc::clone(&self.data);
+        let last_packet = Arc::clone(&self.last_packet);
         let abort = Arc::clone(&self.abort);
         let handle = spawn(move || {
-            let e = catch_unwind(|| {
-                let sock = UdpSocket::bind(addr).unwrap();
-                sock.set_read_timeout(Some(Duration::from_millis(500)))
-                    .expect("Setting timeout won't fail");
-
-                loop {
-                    if abort.load(Ordering::Relaxed) {
-                        break;
-                    }
+            let e = catch_unwind(|| loop {
+                if abort.load(Ordering::Relaxed) {
+                    break;
+                }
 
-                    let mut buf = [0u8; 65536];
-                    match sock.recv_from(&mut buf) {
-                        Ok((size, _)) => {
-                            let (_, packet) = rosc::decoder::decode_udp(&buf[..size]).unwrap();
-                            data.lock().unwrap().update_from_packet(packet);
-                        }
-                        Err(e) => {
-                            if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::WouldBlock
-                            {
-                                continue;
-                            }
-                            println!("Error receiving from socket: {}", e);
+                let mut buf = [0u8; 65536];
+                match sock.recv_from(&mut buf) {
+                    Ok((size, _)) => {
+                        let (_, packet) = rosc::decoder::decode_udp(&buf[..size]).unwrap();
+                        data.lock().unwrap().update_from_packet(packet);
+                        *last_packet.lock().unwrap() = Some(Instant::now());
+                    }
+                    Err(e) => {
+                        if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::WouldBlock {
+                            continue;
                         }
+                        println!("Error receiving from socket: {}", e);
                     }
                 }
             });
@@ -0,0 +1,181 @@
+//! Rules that fire actions when a tracker input crosses a threshold, such as
+//! toggling an expression when the tongue sticks out.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::{Action, ActionRegistry},
+    app::model::Stored,
+};
+
+use super::{InputKind, Inputs};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 2] = [Comparison::Above, Comparison::Below];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Comparison::Above => "Above",
+            Comparison::Below => "Below",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trigger {
+    pub enabled: bool,
+    pub input: InputKind,
+    pub comparison: Comparison,
+    pub threshold: f32,
+    /// How far the input has to come back past the threshold before the
+    /// trigger can fire again, so a noisy input doesn't fire repeatedly.
+    pub hysteresis: f32,
+    /// Seconds the input has to stay past the threshold before firing.
+    pub hold: f32,
+    /// Seconds after firing during which the trigger can't fire again.
+    pub cooldown: f32,
+    pub action: Action,
+}
+
+impl Trigger {
+    pub fn new(action: Action) -> Self {
+        Self {
+            enabled: true,
+            input: InputKind::None,
+            comparison: Comparison::Above,
+            threshold: 0.5,
+            hysteresis: 0.1,
+            hold: 0.2,
+            cooldown: 1.0,
+            action,
+        }
+    }
+
+    fn past_threshold(&self, value: f32) -> bool {
+        match self.comparison {
+            Comparison::Above => value > self.threshold,
+            Comparison::Below => value < self.threshold,
+        }
+    }
+
+    fn released(&self, value: f32) -> bool {
+        match self.comparison {
+            Comparison::Above => value < self.threshold - self.hysteresis,
+            Comparison::Below => value > self.threshold + self.hysteresis,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TriggerState {
+    /// The input's value as of the last update.
+    pub value: Option<f32>,
+    /// Whether the input has come back far enough to fire again.
+    pub armed: bool,
+    /// Seconds the input has been past the threshold while armed.
+    pub held_for: f32,
+    pub cooldown_left: f32,
+    /// Seconds since the trigger last fired.
+    pub since_fired: Option<f32>,
+}
+
+impl Default for TriggerState {
+    fn default() -> Self {
+        Self {
+            value: None,
+            armed: true,
+            held_for: 0.0,
+            cooldown_left: 0.0,
+            since_fired: None,
+        }
+    }
+}
+
+/// The triggers stored for the displayed model, and where each one is at.
+#[derive(Debug, Default)]
+pub struct Triggers {
+    triggers: Vec<Trigger>,
+    states: Vec<TriggerState>,
+}
+
+impl Stored for Triggers {
+    const FILE: &'static str = "triggers.json";
+    const NAME: &'static str = "triggers";
+    type Data = Vec<Trigger>;
+
+    fn from_stored(triggers: Vec<Trigger>) -> Self {
+        Self {
+            triggers,
+            ..Self::default()
+        }
+    }
+
+    fn stored(&self) -> &Vec<Trigger> {
+        &self.triggers
+    }
+}
+
+impl Triggers {
+    pub fn triggers(&self) -> &[Trigger] {
+        &self.triggers
+    }
+
+    pub fn triggers_mut(&mut self) -> &mut Vec<Trigger> {
+        &mut self.triggers
+    }
+
+    pub fn remove(&mut self, ind: usize) -> Trigger {
+        if ind < self.states.len() {
+            self.states.remove(ind);
+        }
+        self.triggers.remove(ind)
+    }
+
+    pub fn state(&self, ind: usize) -> Option<&TriggerState> {
+        self.states.get(ind)
+    }
+
+    /// Evaluates every trigger, dispatching the actions of those that fire.
+    pub fn update(&mut self, dt: f32, inputs: &Inputs, actions: &ActionRegistry) {
+        // Triggers may have been added or removed since the last update.
+        self.states
+            .resize_with(self.triggers.len(), Default::default);
+
+        for (trigger, state) in self.triggers.iter().zip(&mut self.states) {
+            state.value = trigger.input.value(inputs);
+            state.cooldown_left = (state.cooldown_left - dt).max(0.0);
+            if let Some(since) = &mut state.since_fired {
+                *since += dt;
+            }
+
+            let Some(value) = state.value.filter(|_| trigger.enabled) else {
+                state.held_for = 0.0;
+                continue;
+            };
+
+            if !state.armed && trigger.released(value) {
+                state.armed = true;
+            }
+
+            if !state.armed || !trigger.past_threshold(value) {
+                state.held_for = 0.0;
+                continue;
+            }
+
+            state.held_for += dt;
+            if state.held_for >= trigger.hold && state.cooldown_left <= 0.0 {
+                actions.dispatch(trigger.action.clone());
+                state.armed = false;
+                state.held_for = 0.0;
+                state.cooldown_left = trigger.cooldown;
+                state.since_fired = Some(0.0);
+            }
+        }
+    }
+}